@@ -3,10 +3,7 @@ extern crate toml;
 
 use std::{error::Error, fs, io};
 
-use self::mammut::{
-    apps::{AppBuilder, Scopes},
-    Mastodon, Registration,
-};
+use self::mammut::{apps::Scopes, Mastodon, Registration};
 
 #[allow(dead_code)]
 fn main() -> Result<(), Box<dyn Error>> {
@@ -24,23 +21,19 @@ pub fn get_mastodon_data() -> Result<Mastodon, Box<dyn Error>> {
 }
 
 pub fn register() -> Result<Mastodon, Box<dyn Error>> {
-    let app = AppBuilder {
-        client_name: "mammut-examples",
-        redirect_uris: "urn:ietf:wg:oauth:2.0:oob",
-        scopes: Scopes::All,
-        website: Some("https://github.com/Aaronepower/mammut"),
-    };
-
     let website = read_line("Please enter your mastodon instance url:")?;
-    let mut registration = Registration::new(website.trim());
-    registration.register(app)?;
-    let url = registration.authorise()?;
+    let registered = Registration::new(website.trim())
+        .client_name("mammut-examples")
+        .scopes(Scopes::READ | Scopes::WRITE | Scopes::FOLLOW)
+        .website("https://github.com/Aaronepower/mammut")
+        .build()?;
+    let url = registered.authorize_url();
 
     println!("Click this link to authorize on Mastodon: {}", url);
     let input = read_line("Paste the returned authorization code: ")?;
 
     let code = input.trim();
-    let mastodon = registration.create_access_token(code.to_string())?;
+    let mastodon = registered.complete(code)?;
 
     // Save app data for using on the next run.
     let toml = toml::to_string(&*mastodon)?;
@@ -1,79 +1,507 @@
+use std::borrow::Cow;
+use std::error::Error as StdError;
 use std::fmt;
+use std::ops;
+use std::str::FromStr;
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+use url::Url;
 
 /// Builder struct for defining your application.
+///
+/// By default the app has the `read` scope and shows the code to the user
+/// instead of redirecting. `build` checks the app before it's registered.
+///
 /// ```
-/// use mammut::apps::{AppBuilder, Scopes};
+/// use mammut::apps::{App, Scopes};
+///
+/// let name = String::from("mammut_test");
+/// let app = App::builder()
+///     .client_name(name)
+///     .website("https://github.com/Aaronepower/mammut")
+///     .scopes(Scopes::READ | Scopes::WRITE_STATUSES)
+///     .build()
+///     .unwrap();
+/// assert_eq!(app.client_name(), "mammut_test");
 ///
-/// let app = AppBuilder {
-///     client_name: "mammut_test",
-///     redirect_uris: "urn:ietf:wg:oauth:2.0:oob",
-///     scopes: Scopes::Read,
-///     website: None,
-/// };
+/// assert!(App::builder().build().is_err());
+/// assert!(App::builder().client_name("mammut_test").website("github.com").build().is_err());
 /// ```
-#[derive(Debug, Default, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct AppBuilder<'a> {
     /// Name of the application. Will be displayed when the user is deciding to
     /// grant permission.
-    pub client_name: &'a str,
+    pub client_name: Cow<'a, str>,
     /// Where the user should be redirected after authorization
-    /// (for no redirect, use `urn:ietf:wg:oauth:2.0:oob`)
-    pub redirect_uris: &'a str,
+    /// (for no redirect, use `urn:ietf:wg:oauth:2.0:oob`).
+    #[serde(serialize_with = "join_lines")]
+    pub redirect_uris: Vec<Cow<'a, str>>,
     /// Permission scope of the application.
     pub scopes: Scopes,
     /// URL to the homepage of your application.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub website: Option<&'a str>,
+    pub website: Option<Cow<'a, str>>,
+}
+
+// Mastodon expects multiple redirect uris separated by newlines.
+fn join_lines<S: Serializer>(uris: &[Cow<str>], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&uris.join("\n"))
+}
+
+impl Default for AppBuilder<'_> {
+    fn default() -> Self {
+        AppBuilder {
+            client_name: Cow::Borrowed(""),
+            redirect_uris: vec![Cow::Borrowed(OOB)],
+            scopes: Scopes::default(),
+            website: None,
+        }
+    }
+}
+
+impl<'a> AppBuilder<'a> {
+    /// Set the name of the application.
+    pub fn client_name<S: Into<Cow<'a, str>>>(mut self, client_name: S) -> Self {
+        self.client_name = client_name.into();
+        self
+    }
+
+    /// Set where the user should be redirected after authorising.
+    pub fn redirect(mut self, redirect: &Redirect) -> Self {
+        self.redirect_uris = redirect.uris().into_iter().map(Cow::Owned).collect();
+        self
+    }
+
+    /// Set the permissions the application asks for.
+    pub fn scopes(mut self, scopes: Scopes) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// Set the url to the homepage of the application.
+    pub fn website<S: Into<Cow<'a, str>>>(mut self, website: S) -> Self {
+        self.website = Some(website.into());
+        self
+    }
+
+    /// Check the application and turn it into an `App`.
+    ///
+    /// # Errors
+    /// `Error::ClientNameRequired` if the name is empty, and `Error::Url` if
+    /// the website or a redirect uri isn't a valid url.
+    pub fn build(self) -> crate::Result<App> {
+        if self.client_name.trim().is_empty() {
+            return Err(crate::Error::ClientNameRequired);
+        }
+
+        let website = match self.website {
+            Some(ref website) => Some(Url::parse(website)?.into_string()),
+            None => None,
+        };
+
+        Ok(App {
+            client_name: self.client_name.into_owned(),
+            redirect_uris: self.redirect_uris.join("\n").parse()?,
+            scopes: self.scopes,
+            website,
+        })
+    }
+}
+
+impl From<App> for AppBuilder<'static> {
+    fn from(app: App) -> Self {
+        AppBuilder {
+            client_name: Cow::Owned(app.client_name),
+            redirect_uris: app
+                .redirect_uris
+                .uris()
+                .into_iter()
+                .map(Cow::Owned)
+                .collect(),
+            scopes: app.scopes,
+            website: app.website.map(Cow::Owned),
+        }
+    }
+}
+
+/// An application checked by `AppBuilder::build`, ready to be registered.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct App {
+    client_name: String,
+    redirect_uris: Redirect,
+    scopes: Scopes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    website: Option<String>,
+}
+
+impl App {
+    /// Start building an application.
+    pub fn builder<'a>() -> AppBuilder<'a> {
+        AppBuilder::default()
+    }
+
+    /// The name of the application.
+    pub fn client_name(&self) -> &str {
+        &self.client_name
+    }
+
+    /// Where the user is redirected after authorising.
+    pub fn redirect(&self) -> &Redirect {
+        &self.redirect_uris
+    }
+
+    /// The permissions the application asks for.
+    pub fn scopes(&self) -> Scopes {
+        self.scopes
+    }
+
+    /// The url to the homepage of the application.
+    pub fn website(&self) -> Option<&str> {
+        self.website.as_deref()
+    }
+}
+
+/// Where the user is sent after authorising the application.
+///
+/// Uris are checked and normalised when parsed, such as adding the `/` path
+/// to `https://example.com`. The instance compares redirect uris exactly, so
+/// the normalised form is the one registered and used when authorising.
+///
+/// ```
+/// use mammut::apps::Redirect;
+///
+/// let redirect: Redirect = "urn:ietf:wg:oauth:2.0:oob".parse().unwrap();
+/// assert_eq!(redirect, Redirect::Oob);
+///
+/// let redirect: Redirect = "https://Example.com\nmyapp://oauth/callback".parse().unwrap();
+/// assert_eq!(redirect.to_string(), "https://example.com/\nmyapp://oauth/callback");
+///
+/// assert!("example.com/callback".parse::<Redirect>().is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Redirect {
+    /// Show the code to the user instead of redirecting, to be copied into
+    /// the app by hand.
+    #[default]
+    Oob,
+    /// Redirect to this uri.
+    Uri(Url),
+    /// Register several uris, picking the one to use with
+    /// `Registered::with_redirect`. The first is used by default.
+    Multiple(Vec<Url>),
+}
+
+const OOB: &str = "urn:ietf:wg:oauth:2.0:oob";
+
+impl Redirect {
+    /// The uris, in the form they're registered.
+    pub fn uris(&self) -> Vec<String> {
+        match *self {
+            Redirect::Oob => vec![OOB.to_owned()],
+            Redirect::Uri(ref url) => vec![url.to_string()],
+            Redirect::Multiple(ref urls) => urls.iter().map(Url::to_string).collect(),
+        }
+    }
+}
+
+// Multiple uris are separated by newlines, as Mastodon expects.
+impl fmt::Display for Redirect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.uris().join("\n"))
+    }
+}
+
+// Parses uris separated by whitespace, such as the newline separated uris
+// returned by the instance.
+impl FromStr for Redirect {
+    type Err = url::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut urls = s
+            .split_whitespace()
+            .map(Url::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(match urls.len() {
+            0 => Redirect::Oob,
+            1 if urls[0].as_str() == OOB => Redirect::Oob,
+            1 => Redirect::Uri(urls.remove(0)),
+            _ => Redirect::Multiple(urls),
+        })
+    }
+}
+
+impl Serialize for Redirect {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// The permissions of the application, as a set of OAuth scopes.
+///
+/// Scopes are combined with `|`, and sent to the instance separated by
+/// spaces, the same way they are parsed back.
+///
+/// [Details on what each scope provides](https://docs.joinmastodon.org/api/oauth-scopes/)
+///
+/// ```
+/// use mammut::apps::Scopes;
+///
+/// let scopes = Scopes::READ | Scopes::WRITE_STATUSES | Scopes::WRITE_MEDIA | Scopes::PUSH;
+/// assert_eq!(scopes.to_string(), "read push write:media write:statuses");
+/// assert_eq!(scopes.to_string().parse::<Scopes>().unwrap(), scopes);
+/// assert!(scopes.contains(Scopes::WRITE_MEDIA));
+/// assert!("read cook:breakfast".parse::<Scopes>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Scopes {
+    bits: u64,
+}
+
+macro_rules! scopes {
+    ($($(#[$attr:meta])* $name:ident = $scope:expr,)+) => {
+        // The position of each scope's bit.
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+        enum Bit {
+            $($name,)+
+        }
+
+        // The name of each scope, in the order of their bits.
+        const NAMES: &[&str] = &[$($scope,)+];
+
+        impl Scopes {
+            $(
+                $(#[$attr])*
+                pub const $name: Scopes = Scopes { bits: 1 << Bit::$name as u64 };
+            )+
+        }
+    };
+}
+
+scopes! {
+    /// Read everything, `read`.
+    READ = "read",
+    /// Write everything, `write`.
+    WRITE = "write",
+    /// Follow and block accounts, `follow`. Deprecated by Mastodon in favour
+    /// of `READ_FOLLOWS`, `WRITE_FOLLOWS`, `READ_BLOCKS` and so on.
+    FOLLOW = "follow",
+    /// Receive push notifications, `push`.
+    PUSH = "push",
+    /// Only read the user's own account, `profile`.
+    PROFILE = "profile",
+    /// `read:accounts`
+    READ_ACCOUNTS = "read:accounts",
+    /// `read:blocks`
+    READ_BLOCKS = "read:blocks",
+    /// `read:bookmarks`
+    READ_BOOKMARKS = "read:bookmarks",
+    /// `read:favourites`
+    READ_FAVOURITES = "read:favourites",
+    /// `read:filters`
+    READ_FILTERS = "read:filters",
+    /// `read:follows`
+    READ_FOLLOWS = "read:follows",
+    /// `read:lists`
+    READ_LISTS = "read:lists",
+    /// `read:mutes`
+    READ_MUTES = "read:mutes",
+    /// `read:notifications`
+    READ_NOTIFICATIONS = "read:notifications",
+    /// `read:search`
+    READ_SEARCH = "read:search",
+    /// `read:statuses`
+    READ_STATUSES = "read:statuses",
+    /// `write:accounts`
+    WRITE_ACCOUNTS = "write:accounts",
+    /// `write:blocks`
+    WRITE_BLOCKS = "write:blocks",
+    /// `write:bookmarks`
+    WRITE_BOOKMARKS = "write:bookmarks",
+    /// `write:conversations`
+    WRITE_CONVERSATIONS = "write:conversations",
+    /// `write:favourites`
+    WRITE_FAVOURITES = "write:favourites",
+    /// `write:filters`
+    WRITE_FILTERS = "write:filters",
+    /// `write:follows`
+    WRITE_FOLLOWS = "write:follows",
+    /// `write:lists`
+    WRITE_LISTS = "write:lists",
+    /// `write:media`
+    WRITE_MEDIA = "write:media",
+    /// `write:mutes`
+    WRITE_MUTES = "write:mutes",
+    /// `write:notifications`
+    WRITE_NOTIFICATIONS = "write:notifications",
+    /// `write:reports`
+    WRITE_REPORTS = "write:reports",
+    /// `write:statuses`
+    WRITE_STATUSES = "write:statuses",
+    /// Read all moderation data, `admin:read`.
+    ADMIN_READ = "admin:read",
+    /// `admin:read:accounts`
+    ADMIN_READ_ACCOUNTS = "admin:read:accounts",
+    /// `admin:read:reports`
+    ADMIN_READ_REPORTS = "admin:read:reports",
+    /// `admin:read:domain_allows`
+    ADMIN_READ_DOMAIN_ALLOWS = "admin:read:domain_allows",
+    /// `admin:read:domain_blocks`
+    ADMIN_READ_DOMAIN_BLOCKS = "admin:read:domain_blocks",
+    /// `admin:read:ip_blocks`
+    ADMIN_READ_IP_BLOCKS = "admin:read:ip_blocks",
+    /// `admin:read:email_domain_blocks`
+    ADMIN_READ_EMAIL_DOMAIN_BLOCKS = "admin:read:email_domain_blocks",
+    /// `admin:read:canonical_email_blocks`
+    ADMIN_READ_CANONICAL_EMAIL_BLOCKS = "admin:read:canonical_email_blocks",
+    /// Perform all moderation actions, `admin:write`.
+    ADMIN_WRITE = "admin:write",
+    /// `admin:write:accounts`
+    ADMIN_WRITE_ACCOUNTS = "admin:write:accounts",
+    /// `admin:write:reports`
+    ADMIN_WRITE_REPORTS = "admin:write:reports",
+    /// `admin:write:domain_allows`
+    ADMIN_WRITE_DOMAIN_ALLOWS = "admin:write:domain_allows",
+    /// `admin:write:domain_blocks`
+    ADMIN_WRITE_DOMAIN_BLOCKS = "admin:write:domain_blocks",
+    /// `admin:write:ip_blocks`
+    ADMIN_WRITE_IP_BLOCKS = "admin:write:ip_blocks",
+    /// `admin:write:email_domain_blocks`
+    ADMIN_WRITE_EMAIL_DOMAIN_BLOCKS = "admin:write:email_domain_blocks",
+    /// `admin:write:canonical_email_blocks`
+    ADMIN_WRITE_CANONICAL_EMAIL_BLOCKS = "admin:write:canonical_email_blocks",
 }
 
-/// Permission scope of the application.
-/// [Details on what each permission provides](//github.com/tootsuite/documentation/blob/master/Using-the-API/OAuth-details.md)
-#[derive(Debug, Clone, Copy, Serialize)]
-pub enum Scopes {
+#[allow(non_upper_case_globals)]
+impl Scopes {
     /// All Permissions, equivalent to `read write follow`
-    #[serde(rename = "read write follow")]
-    All,
+    #[deprecated(note = "use `Scopes::READ | Scopes::WRITE | Scopes::FOLLOW`")]
+    pub const All: Scopes = Scopes::READ.union(Scopes::WRITE).union(Scopes::FOLLOW);
     /// Only permission to add and remove followers.
-    #[serde(rename = "follow")]
-    Follow,
+    #[deprecated(note = "use `Scopes::FOLLOW`")]
+    pub const Follow: Scopes = Scopes::FOLLOW;
     /// Read only permissions.
-    #[serde(rename = "read")]
-    Read,
+    #[deprecated(note = "use `Scopes::READ`")]
+    pub const Read: Scopes = Scopes::READ;
     /// Read & Follow permissions.
-    #[serde(rename = "read follow")]
-    ReadFollow,
+    #[deprecated(note = "use `Scopes::READ | Scopes::FOLLOW`")]
+    pub const ReadFollow: Scopes = Scopes::READ.union(Scopes::FOLLOW);
     /// Read & Write permissions.
-    #[serde(rename = "read write")]
-    ReadWrite,
+    #[deprecated(note = "use `Scopes::READ | Scopes::WRITE`")]
+    pub const ReadWrite: Scopes = Scopes::READ.union(Scopes::WRITE);
     /// Write only permissions.
-    #[serde(rename = "write")]
-    Write,
+    #[deprecated(note = "use `Scopes::WRITE`")]
+    pub const Write: Scopes = Scopes::WRITE;
     /// Write & Follow permissions.
-    #[serde(rename = "write follow")]
-    WriteFollow,
+    #[deprecated(note = "use `Scopes::WRITE | Scopes::FOLLOW`")]
+    pub const WriteFollow: Scopes = Scopes::WRITE.union(Scopes::FOLLOW);
+}
+
+impl Scopes {
+    /// No scopes at all.
+    pub const fn empty() -> Self {
+        Scopes { bits: 0 }
+    }
+
+    /// Both these scopes and `other`.
+    pub const fn union(self, other: Scopes) -> Self {
+        Scopes {
+            bits: self.bits | other.bits,
+        }
+    }
+
+    /// Whether all of `other` is in these scopes. Scopes are compared
+    /// exactly, so `READ` doesn't contain `READ_STATUSES`.
+    pub fn contains(self, other: Scopes) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    /// Whether there are no scopes.
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    // The names of the scopes, in a fixed order.
+    fn names(self) -> impl Iterator<Item = &'static str> {
+        NAMES
+            .iter()
+            .enumerate()
+            .filter(move |&(bit, _)| self.bits & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+    }
+}
+
+// Read only permissions, the instance's default.
+impl Default for Scopes {
+    fn default() -> Self {
+        Scopes::READ
+    }
+}
+
+impl ops::BitOr for Scopes {
+    type Output = Scopes;
+
+    fn bitor(self, other: Scopes) -> Scopes {
+        self.union(other)
+    }
+}
+
+impl ops::BitOrAssign for Scopes {
+    fn bitor_assign(&mut self, other: Scopes) {
+        *self = self.union(other);
+    }
 }
 
 impl fmt::Display for Scopes {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use self::Scopes::*;
-        write!(
-            f,
-            "{}",
-            match *self {
-                All => "read%20write%20follow",
-                Follow => "follow",
-                Read => "read",
-                ReadFollow => "read%20follow",
-                ReadWrite => "read%20write",
-                Write => "write",
-                WriteFollow => "write%20follow",
+        for (i, name) in self.names().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
             }
-        )
+            f.write_str(name)?;
+        }
+
+        Ok(())
     }
 }
 
-impl Default for Scopes {
-    fn default() -> Self {
-        Scopes::Read
+impl FromStr for Scopes {
+    type Err = ParseScopesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_whitespace()
+            .try_fold(Scopes::empty(), |scopes, name| {
+                match NAMES.iter().position(|known| *known == name) {
+                    Some(bit) => Ok(scopes.union(Scopes { bits: 1 << bit })),
+                    None => Err(ParseScopesError(name.to_owned())),
+                }
+            })
+    }
+}
+
+impl Serialize for Scopes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Scopes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
     }
 }
+
+/// A scope unknown to this crate, from parsing `Scopes`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseScopesError(pub String);
+
+impl fmt::Display for ParseScopesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown OAuth scope: {}", self.0)
+    }
+}
+
+impl StdError for ParseScopesError {}
@@ -8,6 +8,38 @@ use reqwest::multipart::Form;
 use crate::Result;
 
 /// A struct representing an Account.
+///
+/// The counts are also accepted as strings, as some servers send, and are
+/// `0` when missing. `last_status_at` is accepted as a date or, from older
+/// instances, a full timestamp.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::account::Account;
+///
+/// let account: Account = serde_json::from_str(r#"{
+///     "id": "1",
+///     "username": "Gargron",
+///     "acct": "Gargron",
+///     "display_name": "Eugen",
+///     "locked": false,
+///     "created_at": "2016-03-16T14:34:26.392Z",
+///     "note": "",
+///     "url": "https://mastodon.social/@Gargron",
+///     "avatar": "https://files.mastodon.social/accounts/avatars/000/000/001/original/a.png",
+///     "avatar_static": "https://files.mastodon.social/accounts/avatars/000/000/001/original/a.png",
+///     "header": "https://files.mastodon.social/accounts/headers/000/000/001/original/h.png",
+///     "header_static": "https://files.mastodon.social/accounts/headers/000/000/001/original/h.png",
+///     "followers_count": "322930",
+///     "following_count": 459,
+///     "last_status_at": "2019-11-15T07:35:48.264Z"
+/// }"#).unwrap();
+/// assert_eq!(account.followers_count, 322930);
+/// assert_eq!(account.following_count, 459);
+/// assert_eq!(account.statuses_count, 0);
+/// assert_eq!(account.last_status_at.unwrap().to_string(), "2019-11-15");
+/// ```
 #[derive(Debug, Clone, Deserialize)]
 pub struct Account {
     /// Equals `username` for local users, includes `@domain` for remote ones.
@@ -21,8 +53,10 @@ pub struct Account {
     /// The account's display name.
     pub display_name: String,
     /// The number of followers for the account.
+    #[serde(default, deserialize_with = "super::string_or_number")]
     pub followers_count: u64,
     /// The number of accounts the given account is following.
+    #[serde(default, deserialize_with = "super::string_or_number")]
     pub following_count: u64,
     /// URL to the header image.
     pub header: String,
@@ -36,7 +70,12 @@ pub struct Account {
     /// Biography of user.
     pub note: String,
     /// The number of statuses the account has made.
+    #[serde(default, deserialize_with = "super::string_or_number")]
     pub statuses_count: u64,
+    /// The day the account last posted a status, if it has posted. Sent as a
+    /// full timestamp by instances older than 3.1.
+    #[serde(default, deserialize_with = "super::date_or_time")]
+    pub last_status_at: Option<NaiveDate>,
     /// URL of the user's profile page (can be remote).
     pub url: String,
     /// The username of the account.
@@ -47,24 +86,104 @@ pub struct Account {
     /// If the owner decided to switch accounts, new account is in
     /// this attribute
     pub moved: Option<Box<Account>>,
+    /// Custom emojis used in the account's display name and note.
+    #[serde(default)]
+    pub emojis: Vec<super::status::Emoji>,
+    /// Profile metadata, as HTML.
+    #[serde(default)]
+    pub fields: Vec<MetadataField>,
 }
 
 /// An extra object given from `verify_credentials` giving defaults about a user
 #[derive(Debug, Clone, Deserialize)]
 pub struct Source {
-    privacy: crate::status_builder::Visibility,
-    sensitive: bool,
-    note: String,
+    /// The default post privacy to be used for new statuses.
+    pub privacy: crate::status_builder::Visibility,
+    /// Whether new statuses should be marked sensitive by default.
+    pub sensitive: bool,
+    /// Profile bio in plain text.
+    pub note: String,
+    /// Profile metadata in plain text.
+    #[serde(default)]
+    pub fields: Vec<MetadataField>,
+}
+
+/// A name/value pair of profile metadata.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetadataField {
+    /// The label of the field.
+    pub name: String,
+    /// The value of the field.
+    pub value: String,
+    /// When the link in `value` was verified to link back to the profile
+    /// with `rel="me"`, if it has been.
+    #[serde(default)]
+    pub verified_at: Option<DateTime<Utc>>,
 }
 
+/// The accounts the user follows that also follow an account.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FamiliarFollowers {
+    /// The ID of the account.
+    pub id: String,
+    /// The accounts the user follows that follow the account.
+    pub accounts: Vec<Account>,
+}
+
+/// Data to be sent to the server when updating the user's credentials.
+/// Anything left unset is unchanged.
+///
+/// ```
+/// # extern crate mammut;
+/// use mammut::entities::account::CredientialsBuilder;
+///
+/// let changes = CredientialsBuilder::default()
+///     .note("Posts about Rust")
+///     .fields(&[("Website", "https://example.com"), ("Pronouns", "they/them")]);
+/// assert!(changes.into_form().is_ok());
+/// ```
+#[derive(Debug, Default)]
 pub struct CredientialsBuilder<'a> {
     display_name: Option<&'a str>,
     note: Option<&'a str>,
     avatar: Option<&'a Path>,
     header: Option<&'a Path>,
+    fields: Option<&'a [(&'a str, &'a str)]>,
 }
 
 impl<'a> CredientialsBuilder<'a> {
+    /// Set the display name.
+    pub fn display_name(mut self, display_name: &'a str) -> Self {
+        self.display_name = Some(display_name);
+        self
+    }
+
+    /// Set the biography.
+    pub fn note(mut self, note: &'a str) -> Self {
+        self.note = Some(note);
+        self
+    }
+
+    /// Set the avatar to the image at `avatar`.
+    pub fn avatar(mut self, avatar: &'a Path) -> Self {
+        self.avatar = Some(avatar);
+        self
+    }
+
+    /// Set the header to the image at `header`.
+    pub fn header(mut self, header: &'a Path) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// Replace the profile metadata with these name/value pairs. Mastodon
+    /// allows up to four.
+    pub fn fields(mut self, fields: &'a [(&'a str, &'a str)]) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
+    /// Turn this builder into a multipart form to send to the server.
     pub fn into_form(self) -> Result<Form> {
         let mut form = Form::new();
         macro_rules! add_to_form {
@@ -94,6 +213,15 @@ impl<'a> CredientialsBuilder<'a> {
             header: File;
         }
 
+        // Sent as `fields_attributes[0][name]` and so on, which Rails reads
+        // as a nested list.
+        for (i, (name, value)) in self.fields.unwrap_or_default().iter().enumerate() {
+            let key = |field: &str| format!("fields_attributes[{}][{}]", i, field);
+            form = form
+                .text(key("name"), (*name).to_owned())
+                .text(key("value"), (*value).to_owned());
+        }
+
         Ok(form)
     }
 }
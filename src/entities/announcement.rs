@@ -0,0 +1,90 @@
+//! Module containing announcements made by the instance's administrators.
+
+use super::status::{Emoji, Mention, Tag};
+use chrono::prelude::*;
+
+/// An announcement made by the instance's administrators.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::announcement::Announcement;
+///
+/// let announcement: Announcement = serde_json::from_str(r#"{
+///     "id": "8",
+///     "content": "<p>Looks like there was an issue processing audio attachments.</p>",
+///     "starts_at": null,
+///     "ends_at": null,
+///     "all_day": false,
+///     "published_at": "2020-07-03T01:27:38.726Z",
+///     "updated_at": "2020-07-03T01:27:38.752Z",
+///     "read": true,
+///     "mentions": [],
+///     "statuses": [{"id": "1", "url": "https://mastodon.example/@user/1"}],
+///     "tags": [],
+///     "emojis": [],
+///     "reactions": [{"name": "bongoCat", "count": 9, "me": false}]
+/// }"#).unwrap();
+/// assert_eq!(announcement.statuses[0].id, "1");
+/// assert_eq!(announcement.reactions[0].count, 9);
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Announcement {
+    /// The ID of the announcement.
+    pub id: String,
+    /// The text of the announcement, in HTML.
+    pub content: String,
+    /// When the event the announcement is about starts, if it is about one.
+    pub starts_at: Option<DateTime<Utc>>,
+    /// When the event the announcement is about ends, if it is about one.
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Whether `starts_at` and `ends_at` are only dates, without a time.
+    pub all_day: bool,
+    /// The time the announcement was published.
+    pub published_at: DateTime<Utc>,
+    /// The time the announcement was last updated.
+    pub updated_at: DateTime<Utc>,
+    /// Whether the user has dismissed the announcement. Not set without an
+    /// access token.
+    pub read: Option<bool>,
+    /// The accounts mentioned in the announcement.
+    #[serde(default)]
+    pub mentions: Vec<Mention>,
+    /// The statuses linked to in the announcement.
+    #[serde(default)]
+    pub statuses: Vec<AnnouncementStatus>,
+    /// The hashtags used in the announcement.
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    /// The custom emojis used in the announcement.
+    #[serde(default)]
+    pub emojis: Vec<Emoji>,
+    /// The reactions to the announcement.
+    #[serde(default)]
+    pub reactions: Vec<AnnouncementReaction>,
+}
+
+/// A status linked to in an announcement.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnnouncementStatus {
+    /// The ID of the status.
+    pub id: String,
+    /// The URL of the status.
+    pub url: String,
+}
+
+/// An emoji reaction to an announcement.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnnouncementReaction {
+    /// The unicode emoji, or the shortcode of the custom emoji.
+    pub name: String,
+    /// The number of users that reacted with the emoji.
+    pub count: u64,
+    /// Whether the user reacted with the emoji. Not set without an access
+    /// token.
+    pub me: Option<bool>,
+    /// URL to the image of the custom emoji, if it is one.
+    pub url: Option<String>,
+    /// URL to the static image of the custom emoji, if it is one.
+    pub static_url: Option<String>,
+}
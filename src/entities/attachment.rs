@@ -1,8 +1,41 @@
 //! Module containing everything related to media attachements.
-use super::Empty;
-use serde::{Deserialize, Deserializer};
+use serde::{de, Deserialize, Deserializer};
+use std::fmt;
+use std::str::FromStr;
 
 /// A struct representing a media attachment.
+///
+/// An empty or missing `meta` object is deserialised as `None`.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::attachment::{Attachment, MediaType};
+///
+/// let attachment: Attachment = serde_json::from_str(r#"{
+///     "id": "22345792",
+///     "type": "audio",
+///     "url": "https://files.mastodon.social/media_attachments/files/022/345/792/original/57859aede991da25.mp3",
+///     "preview_url": "https://files.mastodon.social/media_attachments/files/022/345/792/small/57859aede991da25.png",
+///     "remote_url": null,
+///     "text_url": null,
+///     "meta": {"length": "0:06:42.86", "duration": 402.86},
+///     "description": null,
+///     "blurhash": "UB9jfvtT0gO^N5tSX4XV9uR%^Ni]D%Rj$*WX"
+/// }"#).unwrap();
+/// assert_eq!(attachment.media_type, MediaType::Audio);
+/// assert_eq!(attachment.meta.unwrap().duration, Some(402.86));
+/// assert!(attachment.blurhash.is_some());
+///
+/// let attachment: Attachment = serde_json::from_str(r#"{
+///     "id": "1",
+///     "type": "image",
+///     "url": "https://example.com/1.png",
+///     "preview_url": "https://example.com/1_small.png",
+///     "meta": {}
+/// }"#).unwrap();
+/// assert!(attachment.meta.is_none());
+/// ```
 #[derive(Debug, Clone, Deserialize)]
 pub struct Attachment {
     /// ID of the attachment.
@@ -10,8 +43,9 @@ pub struct Attachment {
     /// The media type of an attachment.
     #[serde(rename = "type")]
     pub media_type: MediaType,
-    /// URL of the locally hosted version of the image.
-    pub url: String,
+    /// URL of the locally hosted version of the image. Missing while an
+    /// attachment uploaded with `media_v2` is being processed.
+    pub url: Option<String>,
     /// For remote images, the remote URL of the original image.
     pub remote_url: Option<String>,
     /// URL of the preview image.
@@ -20,50 +54,167 @@ pub struct Attachment {
     /// (only present on local images)
     pub text_url: Option<String>,
     /// Meta information about the attachment.
-    #[serde(deserialize_with = "empty_as_none")]
+    #[serde(default, deserialize_with = "empty_as_none")]
     pub meta: Option<Meta>,
-    /// Noop will be removed.
+    /// The alt text describing the attachment.
     pub description: Option<String>,
+    /// A [BlurHash](https://blurha.sh) of the attachment, to show as a
+    /// placeholder while it loads.
+    pub blurhash: Option<String>,
 }
 
 fn empty_as_none<'de, D: Deserializer<'de>>(val: D) -> Result<Option<Meta>, D::Error> {
+    // Check for an empty object before deserialising `Meta`, as every field
+    // of `Meta` is optional and so it would accept one.
+    match json::Value::deserialize(val)? {
+        json::Value::Null => Ok(None),
+        json::Value::Object(ref map) if map.is_empty() => Ok(None),
+        value => Meta::deserialize(value)
+            .map(Some)
+            .map_err(de::Error::custom),
+    }
+}
+
+// Some servers send numbers as strings, such as `"1280"`.
+fn lenient<'de, D, T>(val: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: fmt::Display,
+{
     #[derive(Deserialize)]
     #[serde(untagged)]
-    enum EmptyOrMeta {
-        Empty(Empty),
-        Meta(Meta),
+    enum Lenient<T> {
+        Value(T),
+        String(String),
     }
 
-    Ok(match EmptyOrMeta::deserialize(val)? {
-        EmptyOrMeta::Empty(_) => None,
-        EmptyOrMeta::Meta(m) => Some(m),
-    })
+    match Option::<Lenient<T>>::deserialize(val)? {
+        Some(Lenient::Value(value)) => Ok(Some(value)),
+        Some(Lenient::String(s)) => s.parse().map(Some).map_err(de::Error::custom),
+        None => Ok(None),
+    }
 }
 
-/// Information about the attachment itself.
+/// Information about the attachment itself. Which fields are set depends on
+/// the type of the attachment and the server; images have dimensions, audio
+/// has a duration and video has both.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::attachment::Meta;
+///
+/// let image: Meta = serde_json::from_str(r#"{
+///     "focus": {"x": -0.42, "y": 0.69},
+///     "original": {"width": 640, "height": 480, "size": "640x480", "aspect": 1.3333333333333333},
+///     "small": {"width": 461, "height": 346, "size": "461x346", "aspect": 1.3323699421965318}
+/// }"#).unwrap();
+/// assert_eq!(image.small.unwrap().width, Some(461));
+/// assert_eq!(image.focus.unwrap().x, -0.42);
+///
+/// let video: Meta = serde_json::from_str(r#"{
+///     "length": "0:01:28.65",
+///     "duration": 88.65,
+///     "fps": 24,
+///     "size": "1280x720",
+///     "width": 1280,
+///     "height": 720,
+///     "aspect": 1.7777777777777777,
+///     "audio_encode": "aac (LC) (mp4a / 0x6134706D)",
+///     "audio_bitrate": "44100 Hz",
+///     "audio_channels": "stereo",
+///     "original": {"width": 1280, "height": 720, "frame_rate": "6159375/249269", "duration": 88.654, "bitrate": 862056},
+///     "small": {"width": 400, "height": 225, "size": "400x225", "aspect": 1.7777777777777777}
+/// }"#).unwrap();
+/// assert_eq!(video.duration, Some(88.65));
+/// assert_eq!(video.original.unwrap().bitrate, Some(862056));
+///
+/// let audio: Meta = serde_json::from_str(r#"{
+///     "length": "0:06:42.86",
+///     "duration": 402.86,
+///     "audio_encode": "mp3",
+///     "audio_bitrate": "44100 Hz",
+///     "audio_channels": "stereo",
+///     "original": {"duration": 402.860408, "bitrate": "166290"}
+/// }"#).unwrap();
+/// assert!(audio.small.is_none());
+/// assert_eq!(audio.original.unwrap().bitrate, Some(166290));
+/// ```
 #[derive(Debug, Deserialize, Clone)]
 pub struct Meta {
     /// Original version.
-    original: ImageDetails,
+    pub original: Option<ImageDetails>,
     /// Smaller version.
-    small: ImageDetails,
+    pub small: Option<ImageDetails>,
+    /// The focal point of an image, used when cropping its preview.
+    pub focus: Option<Focus>,
+    /// The length of audio or video as `h:mm:ss.ss`.
+    pub length: Option<String>,
+    /// The length of audio or video in seconds.
+    #[serde(default, deserialize_with = "lenient")]
+    pub duration: Option<f64>,
+    /// The frames per second of a video.
+    #[serde(default, deserialize_with = "lenient")]
+    pub fps: Option<f64>,
+    /// A string of `widthxheight` for a video.
+    pub size: Option<String>,
+    /// The width of a video.
+    #[serde(default, deserialize_with = "lenient")]
+    pub width: Option<u64>,
+    /// The height of a video.
+    #[serde(default, deserialize_with = "lenient")]
+    pub height: Option<u64>,
+    /// The aspect ratio of a video.
+    #[serde(default, deserialize_with = "lenient")]
+    pub aspect: Option<f64>,
+    /// The codec of the audio.
+    pub audio_encode: Option<String>,
+    /// The sample rate of the audio, such as `44100 Hz`.
+    pub audio_bitrate: Option<String>,
+    /// The channels of the audio, such as `stereo`.
+    pub audio_channels: Option<String>,
+}
+
+/// The focal point of an image. Both coordinates range from `-1.0` to `1.0`,
+/// with `(0.0, 0.0)` at the centre, `x` increasing to the right and `y`
+/// increasing upwards.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub struct Focus {
+    /// The horizontal position of the focal point.
+    pub x: f64,
+    /// The vertical position of the focal point.
+    pub y: f64,
 }
 
-/// Dimensions of an attachement.
+/// Dimensions of an attachement, and the duration and bitrate of audio or
+/// video.
 #[derive(Debug, Deserialize, Clone)]
 pub struct ImageDetails {
     /// width of attachment.
-    width: u64,
+    #[serde(default, deserialize_with = "lenient")]
+    pub width: Option<u64>,
     /// height of attachment.
-    height: u64,
+    #[serde(default, deserialize_with = "lenient")]
+    pub height: Option<u64>,
     /// A string of `widthxheight`.
-    size: String,
+    pub size: Option<String>,
     /// The aspect ratio of the attachment.
-    aspect: f64,
+    #[serde(default, deserialize_with = "lenient")]
+    pub aspect: Option<f64>,
+    /// The frame rate of a video, as a fraction such as `30000/1001`.
+    pub frame_rate: Option<String>,
+    /// The length of audio or video in seconds.
+    #[serde(default, deserialize_with = "lenient")]
+    pub duration: Option<f64>,
+    /// The bitrate of audio or video in bits per second.
+    #[serde(default, deserialize_with = "lenient")]
+    pub bitrate: Option<u64>,
 }
 
-/// The type of media attachment.
-#[derive(Debug, Deserialize, Clone, Copy)]
+/// The type of media attachment. Types this crate doesn't know about are
+/// deserialised as `Unknown`.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum MediaType {
     /// An image.
     #[serde(rename = "image")]
@@ -74,7 +225,10 @@ pub enum MediaType {
     /// A gifv format file.
     #[serde(rename = "gifv")]
     Gifv,
+    /// An audio file.
+    #[serde(rename = "audio")]
+    Audio,
     /// Unknown format.
-    #[serde(rename = "unknown")]
+    #[serde(rename = "unknown", other)]
     Unknown,
 }
@@ -1,5 +1,7 @@
 //! Module representing cards of statuses.
 
+use serde::{Deserialize, Deserializer};
+
 /// A card of a status.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Card {
@@ -12,17 +14,36 @@ pub struct Card {
     /// The image associated with the card, if any.
     pub image: Option<String>,
     /// OEmbed data
-    author_name: Option<String>,
+    pub author_name: Option<String>,
     /// OEmbed data
-    author_url: Option<String>,
+    pub author_url: Option<String>,
     /// OEmbed data
-    provider_name: Option<String>,
+    pub provider_name: Option<String>,
     /// OEmbed data
-    provider_url: Option<String>,
+    pub provider_url: Option<String>,
     /// OEmbed data
-    html: Option<String>,
+    pub html: Option<String>,
     /// OEmbed data
-    width: Option<String>,
+    #[serde(deserialize_with = "number_or_string", default)]
+    pub width: Option<String>,
     /// OEmbed data
-    height: Option<String>,
+    #[serde(deserialize_with = "number_or_string", default)]
+    pub height: Option<String>,
+}
+
+// Newer instances send `width` and `height` as numbers.
+fn number_or_string<'de, D: Deserializer<'de>>(val: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Number(u64),
+        String(String),
+    }
+
+    Ok(
+        Option::<NumberOrString>::deserialize(val)?.map(|val| match val {
+            NumberOrString::Number(n) => n.to_string(),
+            NumberOrString::String(s) => s,
+        }),
+    )
 }
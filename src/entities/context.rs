@@ -1,5 +1,7 @@
 //! A module about contexts of statuses.
 
+use std::collections::HashSet;
+
 use super::status::Status;
 
 /// A context of a status returning a list of statuses it replied to and
@@ -11,3 +13,94 @@ pub struct Context {
     /// Statuses that replied to this status.
     pub descendants: Vec<Status>,
 }
+
+impl Context {
+    /// The descendants as a tree of replies, from the `in_reply_to_id` of
+    /// each status. `id` is the ID of the status the context was fetched
+    /// for, and the direct replies to it are returned in the order the server
+    /// sent them.
+    ///
+    /// Replies to statuses missing from the context, such as deleted ones,
+    /// are added to the top level so none are lost. So are statuses that
+    /// only reply to each other in a loop, which a broken server could send,
+    /// starting from the first of them.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # use std::error::Error;
+    /// use mammut::entities::context::ReplyTree;
+    ///
+    /// fn print_replies(replies: &[ReplyTree], depth: usize) {
+    ///     for reply in replies {
+    ///         println!("{}{}", "  ".repeat(depth), reply.status.content);
+    ///         print_replies(&reply.replies, depth + 1);
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let data = Data {
+    /// #   base: "".into(),
+    /// #   client_id: "".into(),
+    /// #   client_secret: "".into(),
+    /// #   redirect: "".into(),
+    /// #   token: "".into(),
+    /// #   token_kind: Default::default(),
+    /// # };
+    /// let client = Mastodon::from_data(data);
+    /// let context = client.get_context("103270115826048975")?;
+    /// print_replies(&context.reply_tree("103270115826048975"), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reply_tree(&self, id: &str) -> Vec<ReplyTree<'_>> {
+        let is_descendant = |parent: &str| self.descendants.iter().any(|s| s.id == parent);
+        let roots: Vec<_> = self
+            .descendants
+            .iter()
+            .filter(|status| {
+                status
+                    .in_reply_to_id
+                    .as_deref()
+                    .map_or(true, |parent| parent == id || !is_descendant(parent))
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut tree = Vec::new();
+        for status in roots {
+            tree.push(self.subtree(status, &mut seen));
+        }
+
+        // Whatever wasn't reached only replies to itself through a loop.
+        for status in &self.descendants {
+            if !seen.contains(&*status.id) {
+                tree.push(self.subtree(status, &mut seen));
+            }
+        }
+
+        tree
+    }
+
+    fn subtree<'a>(&'a self, status: &'a Status, seen: &mut HashSet<&'a str>) -> ReplyTree<'a> {
+        seen.insert(&status.id);
+
+        let mut replies = Vec::new();
+        for reply in &self.descendants {
+            if reply.in_reply_to_id.as_deref() == Some(&*status.id) && !seen.contains(&*reply.id) {
+                replies.push(self.subtree(reply, seen));
+            }
+        }
+
+        ReplyTree { status, replies }
+    }
+}
+
+/// A status in a thread and the replies to it, with their own replies.
+#[derive(Debug, Clone)]
+pub struct ReplyTree<'a> {
+    /// The status.
+    pub status: &'a Status,
+    /// The direct replies to the status.
+    pub replies: Vec<ReplyTree<'a>>,
+}
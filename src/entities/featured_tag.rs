@@ -0,0 +1,40 @@
+//! Module containing everything related to hashtags featured on a profile.
+
+use chrono::prelude::*;
+
+/// A hashtag featured on the user's profile.
+///
+/// `statuses_count` may be sent as a number or a string, and
+/// `last_status_at` as a date or, on older instances, a full timestamp.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::featured_tag::FeaturedTag;
+///
+/// let tag: FeaturedTag = serde_json::from_str(r#"{
+///     "id": "627",
+///     "name": "nowplaying",
+///     "url": "https://mastodon.social/@trwnh/tagged/nowplaying",
+///     "statuses_count": "36",
+///     "last_status_at": "2019-11-15T07:35:48.264Z"
+/// }"#).unwrap();
+/// assert_eq!(tag.statuses_count, 36);
+/// assert_eq!(tag.last_status_at.unwrap().to_string(), "2019-11-15");
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeaturedTag {
+    /// The ID of the featured tag.
+    pub id: String,
+    /// The name of the hashtag, not including the preceding `#`.
+    pub name: String,
+    /// URL to the statuses of the account using the hashtag. Missing on
+    /// instances older than 3.3.
+    pub url: Option<String>,
+    /// The number of the user's statuses using the hashtag.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub statuses_count: u64,
+    /// The day the user last used the hashtag, if they have used it.
+    #[serde(deserialize_with = "super::date_or_time", default)]
+    pub last_status_at: Option<NaiveDate>,
+}
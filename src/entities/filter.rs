@@ -0,0 +1,464 @@
+//! Module containing everything related to keyword filters.
+
+use super::status::Status;
+use chrono::prelude::*;
+
+/// A keyword filter, hiding statuses that contain its phrase.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Filter {
+    /// The ID of the filter.
+    pub id: String,
+    /// The text to be filtered.
+    pub phrase: String,
+    /// The contexts in which the filter should be applied.
+    pub context: Vec<FilterContext>,
+    /// When the filter should no longer be applied, if it expires.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether matching statuses are dropped by the server instead of being
+    /// hidden by the client.
+    pub irreversible: bool,
+    /// Whether the phrase should only match whole words.
+    pub whole_word: bool,
+}
+
+/// The context in which a filter is applied.
+///
+/// Contexts this crate doesn't know about are deserialised as `Unknown`
+/// instead of failing.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::filter::FilterContext;
+///
+/// let context: FilterContext = serde_json::from_str(r#""explore""#).unwrap();
+/// assert_eq!(context, FilterContext::Unknown);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum FilterContext {
+    /// The home timeline and lists.
+    #[serde(rename = "home")]
+    Home,
+    /// Notifications.
+    #[serde(rename = "notifications")]
+    Notifications,
+    /// The public timelines.
+    #[serde(rename = "public")]
+    Public,
+    /// Expanded threads of a status.
+    #[serde(rename = "thread")]
+    Thread,
+    /// Profiles of accounts.
+    #[serde(rename = "account")]
+    Account,
+    /// A context unknown to this crate, such as one added by a newer
+    /// instance. Can't be used when creating or updating a filter.
+    #[serde(other, skip_serializing)]
+    Unknown,
+}
+
+/// A filter from the v2 filters API, grouping keywords and statuses under a
+/// title.
+///
+/// `keywords` and `statuses` are only sent when the filter is requested
+/// directly, the filters attached to a status in `Status::filtered` leave
+/// them empty.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::filter::{FilterAction, FilterV2};
+///
+/// let filter: FilterV2 = serde_json::from_str(r#"{
+///     "id": "19972",
+///     "title": "Test filter",
+///     "context": ["home"],
+///     "expires_at": null,
+///     "filter_action": "warn"
+/// }"#).unwrap();
+/// assert_eq!(filter.filter_action, FilterAction::Warn);
+/// assert!(filter.keywords.is_empty());
+///
+/// // Actions added by newer instances don't fail the whole filter.
+/// let action: FilterAction = serde_json::from_str(r#""dim""#).unwrap();
+/// assert_eq!(action, FilterAction::Unknown);
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilterV2 {
+    /// The ID of the filter.
+    pub id: String,
+    /// The user-defined title of the filter.
+    pub title: String,
+    /// The contexts in which the filter should be applied.
+    pub context: Vec<FilterContext>,
+    /// When the filter should no longer be applied, if it expires.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// What should be done with statuses matching the filter.
+    pub filter_action: FilterAction,
+    /// The keywords grouped under the filter.
+    #[serde(default)]
+    pub keywords: Vec<FilterKeyword>,
+    /// The statuses grouped under the filter.
+    #[serde(default)]
+    pub statuses: Vec<FilterStatus>,
+}
+
+/// What should be done with statuses matching a filter.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum FilterAction {
+    /// Show a warning naming the filter in place of the status.
+    #[serde(rename = "warn")]
+    Warn,
+    /// Don't show the status at all.
+    #[serde(rename = "hide")]
+    Hide,
+    /// Hide the status's media behind a warning. Added in Mastodon 4.4.
+    #[serde(rename = "blur")]
+    Blur,
+    /// An action unknown to this crate, such as one added by a newer
+    /// instance. Isn't sent when creating or updating a filter.
+    #[serde(other, skip_serializing)]
+    Unknown,
+}
+
+/// A keyword that a filter matches against.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilterKeyword {
+    /// The ID of the keyword.
+    pub id: String,
+    /// The phrase to be matched against.
+    pub keyword: String,
+    /// Whether the keyword should only match whole words.
+    pub whole_word: bool,
+}
+
+/// A single status that a filter matches, regardless of its content.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilterStatus {
+    /// The ID of the filter status.
+    pub id: String,
+    /// The ID of the filtered status.
+    pub status_id: String,
+}
+
+/// A filter that matched a status, and what it matched.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilterResult {
+    /// The filter that was matched.
+    pub filter: FilterV2,
+    /// The keywords of the filter that matched the status.
+    pub keyword_matches: Option<Vec<String>>,
+    /// The IDs of the filter statuses that matched the status.
+    pub status_matches: Option<Vec<String>>,
+}
+
+/// A keyword to send with `new_filter_v2` or `update_filter_v2`.
+///
+/// Keywords without an `id` are added to the filter, while those with one
+/// change the existing keyword, or remove it if `destroy` is set.
+#[derive(Debug, Clone, Default)]
+pub struct FilterKeywordAttributes<'a> {
+    /// The ID of an existing keyword of the filter.
+    pub id: Option<&'a str>,
+    /// The phrase to be matched against.
+    pub keyword: &'a str,
+    /// Whether the keyword should only match whole words.
+    pub whole_word: bool,
+    /// Whether the existing keyword should be removed from the filter.
+    pub destroy: bool,
+}
+
+/// A filter that can be applied to statuses on the client, as the server
+/// expects for filters that aren't irreversible.
+pub trait StatusFilter {
+    /// The contexts in which the filter should be applied.
+    fn context(&self) -> &[FilterContext];
+
+    /// Whether the status, or the status it reblogs, matches the filter.
+    fn matches(&self, status: &Status) -> bool;
+}
+
+impl StatusFilter for Filter {
+    fn context(&self) -> &[FilterContext] {
+        &self.context
+    }
+
+    fn matches(&self, status: &Status) -> bool {
+        phrase_matches(&self.phrase, self.whole_word, &searchable_text(status))
+    }
+}
+
+impl StatusFilter for FilterV2 {
+    fn context(&self) -> &[FilterContext] {
+        &self.context
+    }
+
+    fn matches(&self, status: &Status) -> bool {
+        let status = status.reblog.as_ref().map_or(status, |reblog| reblog);
+        let matches_status = self
+            .statuses
+            .iter()
+            .any(|filtered| filtered.status_id == status.id);
+
+        if matches_status {
+            return true;
+        }
+
+        let text = searchable_text(status);
+        self.keywords
+            .iter()
+            .any(|keyword| phrase_matches(&keyword.keyword, keyword.whole_word, &text))
+    }
+}
+
+/// A status matched by a filter, in one of the filter's contexts.
+#[derive(Debug, Clone)]
+pub struct FilterMatch<'a, F> {
+    /// The status that was matched.
+    pub status: &'a Status,
+    /// The filter that matched the status.
+    pub filter: &'a F,
+    /// The context the filter applies in.
+    pub context: FilterContext,
+}
+
+/// Apply `filters` to `statuses`, returning every status that matches a
+/// filter once for each of the filter's contexts, in the order of
+/// `statuses`.
+///
+/// Expired filters aren't skipped, so remove them first if the server
+/// returned any.
+///
+/// Filters are matched against the spoiler text and the content of the
+/// status, or of the status it reblogs. The content's HTML entities are
+/// decoded, and line breaks and paragraphs separate words.
+///
+/// ```
+/// # extern crate mammut;
+/// # #[macro_use] extern crate serde_json;
+/// use mammut::entities::filter::{apply_filters, Filter, FilterContext, StatusFilter};
+/// use mammut::entities::status::Status;
+///
+/// # fn status(spoiler_text: &str, content: &str, reblog: Option<Status>) -> Status {
+/// #     serde_json::from_value(json!({
+/// #         "id": "1", "uri": "", "url": null, "in_reply_to_id": null,
+/// #         "in_reply_to_account_id": null, "reblog": null,
+/// #         "content": content, "created_at": "2019-11-15T07:35:48Z", "edited_at": null,
+/// #         "emojis": [], "reblogs_count": 0, "favourites_count": 0, "reblogged": null,
+/// #         "favourited": null, "muted": null, "sensitive": false,
+/// #         "spoiler_text": spoiler_text, "visibility": "public", "media_attachments": [],
+/// #         "application": null, "language": null, "pinned": null,
+/// #         "account": {
+/// #             "id": "1", "username": "a", "acct": "a", "display_name": "", "locked": false,
+/// #             "created_at": "2016-03-16T14:34:26Z", "note": "", "url": "", "avatar": "",
+/// #             "avatar_static": "", "header": "", "header_static": ""
+/// #         }
+/// #     }))
+/// #     .map(|mut status: Status| {
+/// #         status.reblog = reblog.map(Box::new);
+/// #         status
+/// #     })
+/// #     .unwrap()
+/// # }
+/// let filter = |phrase: &str| -> Filter {
+///     serde_json::from_value(json!({
+///         "id": "1",
+///         "phrase": phrase,
+///         "context": ["home", "public"],
+///         "expires_at": null,
+///         "irreversible": false,
+///         "whole_word": true,
+///     }))
+///     .unwrap()
+/// };
+///
+/// // Numeric, hex and named entities are decoded, and an `&` without a `;`
+/// // is kept as it is.
+/// let decoded = status("", "<p>It&#39;s &#x201C;fish &amp; chips&#x201D;, Q&A; AT&T</p>", None);
+/// assert!(filter("it's").matches(&decoded));
+/// assert!(filter("\u{201C}fish & chips\u{201D}").matches(&decoded));
+/// assert!(filter("q&a;").matches(&decoded));
+/// assert!(filter("at&t").matches(&decoded));
+/// assert!(!filter("amp").matches(&decoded));
+///
+/// // Line breaks and paragraphs separate words.
+/// assert!(!filter("foobar").matches(&status("", "<p>foo</p><p>bar</p>", None)));
+/// assert!(!filter("foobar").matches(&status("", "foo<br>bar", None)));
+/// assert!(filter("bar").matches(&status("", "foo<br />bar", None)));
+///
+/// // The spoiler text is matched too.
+/// assert!(filter("spoiler").matches(&status("Spoiler", "<p>Nothing here</p>", None)));
+///
+/// // So is the status a reblog reblogs.
+/// let reblogged = status("", "<p>rust</p>", None);
+/// assert!(filter("rust").matches(&status("", "", Some(reblogged))));
+///
+/// // A match is returned once for each of the filter's contexts.
+/// let statuses = vec![status("", "<p>rust</p>", None), status("", "<p>go</p>", None)];
+/// let filters = vec![filter("rust")];
+/// let matches = apply_filters(&filters, &statuses);
+/// let contexts: Vec<_> = matches.iter().map(|m| m.context).collect();
+/// assert_eq!(contexts, vec![FilterContext::Home, FilterContext::Public]);
+/// assert!(!contexts.contains(&FilterContext::Notifications));
+/// assert!(matches.iter().all(|m| m.status.content == "<p>rust</p>"));
+/// ```
+pub fn apply_filters<'a, F: StatusFilter>(
+    filters: &'a [F],
+    statuses: &'a [Status],
+) -> Vec<FilterMatch<'a, F>> {
+    let mut matches = Vec::new();
+
+    for status in statuses {
+        for filter in filters.iter().filter(|filter| filter.matches(status)) {
+            for &context in filter.context() {
+                matches.push(FilterMatch {
+                    status,
+                    filter,
+                    context,
+                });
+            }
+        }
+    }
+
+    matches
+}
+
+/// Whether `phrase` appears in `text`, following Mastodon's rules. Matching
+/// is case-insensitive, and a `whole_word` phrase only matches at word
+/// boundaries where the phrase itself starts or ends with a word character.
+/// Word characters are letters, digits and underscores in any script.
+///
+/// ```
+/// # extern crate mammut;
+/// use mammut::entities::filter::phrase_matches;
+///
+/// assert!(phrase_matches("rust", false, "Trusty"));
+/// assert!(phrase_matches("RUST", true, "I like rust."));
+/// assert!(!phrase_matches("rust", true, "Trusty"));
+/// assert!(!phrase_matches("rust", true, "rust_lang"));
+/// assert!(phrase_matches("#rust", true, "a#rust"));
+/// assert!(phrase_matches("über", true, "Über alles"));
+/// assert!(!phrase_matches("ber", true, "über"));
+/// assert!(phrase_matches("rust", true, "trust rust"));
+/// assert!(phrase_matches("a a", true, "ba a a"));
+/// assert!(!phrase_matches("", false, "anything"));
+/// ```
+pub fn phrase_matches(phrase: &str, whole_word: bool, text: &str) -> bool {
+    let phrase = phrase.to_lowercase();
+    let text = text.to_lowercase();
+
+    if phrase.is_empty() {
+        return false;
+    }
+
+    let check_start = whole_word && phrase.chars().next().map_or(false, is_word_char);
+    let check_end = whole_word && phrase.chars().last().map_or(false, is_word_char);
+
+    // Matches may overlap one that isn't on a word boundary, so try every
+    // position rather than using `match_indices`.
+    let mut from = 0;
+    while let Some(offset) = text[from..].find(&*phrase) {
+        let start = from + offset;
+        let end = start + phrase.len();
+        let starts_word = !check_start || !text[..start].chars().last().map_or(false, is_word_char);
+        let ends_word = !check_end || !text[end..].chars().next().map_or(false, is_word_char);
+
+        if starts_word && ends_word {
+            return true;
+        }
+
+        from = start + text[start..].chars().next().map_or(1, char::len_utf8);
+    }
+
+    false
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// The text of a status that filters are matched against, its spoiler text
+// and its content with the HTML removed.
+fn searchable_text(status: &Status) -> String {
+    let status = status.reblog.as_ref().map_or(status, |reblog| reblog);
+
+    format!("{}\n\n{}", status.spoiler_text, strip_html(&status.content))
+}
+
+// Remove the tags from HTML content and decode its entities, treating line
+// breaks and paragraphs as new lines so words either side stay separate.
+fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => {
+                text.push_str(&rest[start..]);
+                rest = "";
+                break;
+            }
+        };
+
+        let tag = rest[start + 1..end].trim().to_lowercase();
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .find(|name| !name.is_empty());
+
+        match name {
+            Some("br") => text.push('\n'),
+            Some("p") if !tag.starts_with('/') && !text.is_empty() => text.push_str("\n\n"),
+            _ => {}
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    text.push_str(rest);
+
+    decode_entities(&text)
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                u32::from_str_radix(&entity[2..], 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
+            }
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(std::char::from_u32),
+            _ => None,
+        });
+
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
@@ -1,5 +1,6 @@
 //! Module containing everything related to an instance.
 use super::account::Account;
+use chrono::prelude::*;
 
 /// A struct containing info of an instance.
 #[derive(Debug, Clone, Deserialize)]
@@ -25,6 +26,358 @@ pub struct Instance {
     pub languages: Option<Vec<String>>,
     /// Contact account for the server.
     pub contact_account: Option<Account>,
+    /// The limits of the instance. Only sent by Mastodon 3.4 onwards.
+    pub configuration: Option<InstanceConfiguration>,
+    /// The maximum number of characters of a status, sent by Pleroma and
+    /// others instead of `configuration`.
+    #[serde(deserialize_with = "super::optional_string_or_number", default)]
+    pub max_toot_chars: Option<u64>,
+}
+
+/// A struct containing info of an instance, from `/api/v2/instance`.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::instance::InstanceV2;
+///
+/// let instance: InstanceV2 = serde_json::from_str(r#"{
+///     "domain": "mastodon.social",
+///     "title": "Mastodon",
+///     "version": "4.0.0rc1",
+///     "source_url": "https://github.com/mastodon/mastodon",
+///     "description": "The original server operated by the Mastodon gGmbH non-profit",
+///     "usage": {"users": {"active_month": 123122}},
+///     "languages": ["en"],
+///     "configuration": {
+///         "statuses": {
+///             "max_characters": "500",
+///             "max_media_attachments": 4,
+///             "characters_reserved_per_url": 23
+///         },
+///         "translation": {"enabled": true}
+///     },
+///     "registrations": {"enabled": false, "approval_required": false, "message": null},
+///     "contact": {"email": "staff@mastodon.social"}
+/// }"#).unwrap();
+/// let statuses = instance.configuration.statuses.unwrap();
+/// assert_eq!(statuses.max_characters, 500);
+/// assert_eq!(statuses.max_media_attachments, 4);
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstanceV2 {
+    /// The domain name of the instance.
+    pub domain: String,
+    /// The instance's title.
+    pub title: String,
+    /// The version of the software used by the instance.
+    pub version: String,
+    /// URL to the source code of the software used by the instance.
+    pub source_url: Option<String>,
+    /// A short description for the instance.
+    pub description: String,
+    /// Usage data of the instance.
+    pub usage: Option<InstanceUsage>,
+    /// The banner image of the instance.
+    pub thumbnail: Option<InstanceThumbnail>,
+    /// Languages used on the instance, as ISO 639-1 codes.
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// The limits and features of the instance.
+    pub configuration: InstanceConfiguration,
+    /// How to sign up to the instance.
+    pub registrations: InstanceRegistrations,
+    /// How to contact the administrators of the instance.
+    pub contact: InstanceContact,
+    /// The rules of the instance.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// Usage data of an instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstanceUsage {
+    /// Usage data about the users of the instance.
+    pub users: InstanceUsageUsers,
+}
+
+/// Usage data about the users of an instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstanceUsageUsers {
+    /// The number of users active in the last four weeks.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub active_month: u64,
+}
+
+/// The banner image of an instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstanceThumbnail {
+    /// URL to the image.
+    pub url: String,
+    /// A hash of the image, to show while it loads.
+    pub blurhash: Option<String>,
+}
+
+/// The limits and features of an instance. Instances only send some of
+/// these, depending on their software and version.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InstanceConfiguration {
+    /// Urls of the instance.
+    pub urls: Option<ConfigurationUrls>,
+    /// Limits of accounts.
+    pub accounts: Option<AccountsConfiguration>,
+    /// Limits of statuses.
+    pub statuses: Option<StatusesConfiguration>,
+    /// Limits of media attachments.
+    pub media_attachments: Option<MediaAttachmentsConfiguration>,
+    /// Limits of polls.
+    pub polls: Option<PollsConfiguration>,
+    /// Whether statuses can be translated.
+    pub translation: Option<TranslationConfiguration>,
+}
+
+/// Urls of an instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfigurationUrls {
+    /// Url for the streaming API, typically a `wss://` url.
+    pub streaming: Option<String>,
+}
+
+/// Limits of accounts on an instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountsConfiguration {
+    /// The maximum number of hashtags that can be featured on a profile.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub max_featured_tags: u64,
+}
+
+/// Limits of statuses on an instance.
+///
+/// Defaults to the limits of Mastodon, for instances that don't send them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct StatusesConfiguration {
+    /// The maximum number of characters of a status.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub max_characters: u64,
+    /// The maximum number of media attachments of a status.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub max_media_attachments: u64,
+    /// The number of characters each url in a status counts as, however
+    /// long it is.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub characters_reserved_per_url: u64,
+}
+
+impl Default for StatusesConfiguration {
+    fn default() -> Self {
+        StatusesConfiguration {
+            max_characters: 500,
+            max_media_attachments: 4,
+            characters_reserved_per_url: 23,
+        }
+    }
+}
+
+/// Limits of media attachments on an instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MediaAttachmentsConfiguration {
+    /// The mime types that can be uploaded.
+    #[serde(default)]
+    pub supported_mime_types: Vec<String>,
+    /// The maximum size of an image, in bytes.
+    #[serde(deserialize_with = "super::optional_string_or_number", default)]
+    pub image_size_limit: Option<u64>,
+    /// The maximum number of pixels of an image.
+    #[serde(deserialize_with = "super::optional_string_or_number", default)]
+    pub image_matrix_limit: Option<u64>,
+    /// The maximum size of a video, in bytes.
+    #[serde(deserialize_with = "super::optional_string_or_number", default)]
+    pub video_size_limit: Option<u64>,
+    /// The maximum frame rate of a video.
+    #[serde(deserialize_with = "super::optional_string_or_number", default)]
+    pub video_frame_rate_limit: Option<u64>,
+    /// The maximum number of pixels of a video.
+    #[serde(deserialize_with = "super::optional_string_or_number", default)]
+    pub video_matrix_limit: Option<u64>,
+}
+
+/// Limits of polls on an instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PollsConfiguration {
+    /// The maximum number of options of a poll.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub max_options: u64,
+    /// The maximum number of characters of each option.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub max_characters_per_option: u64,
+    /// The shortest a poll can last, in seconds.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub min_expiration: u64,
+    /// The longest a poll can last, in seconds.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub max_expiration: u64,
+}
+
+/// Whether statuses can be translated on an instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranslationConfiguration {
+    /// Whether the translation API is available.
+    pub enabled: bool,
+}
+
+/// How to sign up to an instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstanceRegistrations {
+    /// Whether anyone can sign up.
+    pub enabled: bool,
+    /// Whether new accounts must be approved by a moderator.
+    pub approval_required: bool,
+    /// A message shown instead of the sign up form when signing up is
+    /// closed, in HTML.
+    pub message: Option<String>,
+    /// URL to sign up somewhere else, if the instance uses one.
+    pub url: Option<String>,
+}
+
+/// How to contact the administrators of an instance.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstanceContact {
+    /// An email address which can be used to contact the administrators.
+    pub email: String,
+    /// Contact account for the instance.
+    pub account: Option<Account>,
+}
+
+/// A rule of an instance, which reports can cite.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    /// The ID of the rule.
+    pub id: String,
+    /// The rule.
+    pub text: String,
+}
+
+/// The extended description of an instance, from
+/// `instance_extended_description`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtendedDescription {
+    /// The time the description was last updated.
+    pub updated_at: DateTime<Utc>,
+    /// The description, in HTML.
+    pub content: String,
+}
+
+/// A domain blocked by an instance, from `instance_domain_blocks`.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::instance::{DomainBlock, DomainBlockSeverity};
+///
+/// let block: DomainBlock = serde_json::from_str(r#"{
+///     "domain": "daji******.com",
+///     "digest": "3752f03a9d5d4a0a1d84e8fd1dd3e43ae9bd9ed3e4b5d6c4b2c6e0e1f8c9cdd1",
+///     "severity": "suspend",
+///     "comment": "Inappropriate content"
+/// }"#).unwrap();
+/// assert_eq!(block.severity, DomainBlockSeverity::Suspend);
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct DomainBlock {
+    /// The blocked domain. Parts of it may be replaced with `*`.
+    pub domain: String,
+    /// The SHA256 hash of the full domain, to check a domain against.
+    pub digest: String,
+    /// How the domain is blocked.
+    pub severity: DomainBlockSeverity,
+    /// The reason the domain was blocked, if the instance gives one.
+    pub comment: Option<String>,
+}
+
+/// How a domain is blocked by an instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum DomainBlockSeverity {
+    /// Users from the domain are hidden unless followed.
+    #[serde(rename = "silence")]
+    Silence,
+    /// Nothing from the domain reaches the instance.
+    #[serde(rename = "suspend")]
+    Suspend,
+    /// A severity unknown to this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// The activity on an instance during a week, from `instance_activity`.
+///
+/// The server sends these numbers as strings, they are parsed into `u64`s.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::instance::ActivityWeek;
+///
+/// let activity: ActivityWeek = serde_json::from_str(r#"{
+///     "week": "1574640000",
+///     "statuses": "37125",
+///     "logins": "14239",
+///     "registrations": "542"
+/// }"#).unwrap();
+/// assert_eq!(activity.week, 1574640000);
+/// assert_eq!(activity.registrations, 542);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct ActivityWeek {
+    /// The week, as a UNIX timestamp of midnight on its first day.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub week: u64,
+    /// The number of statuses posted during the week.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub statuses: u64,
+    /// The number of users that logged in during the week.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub logins: u64,
+    /// The number of users that signed up during the week.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub registrations: u64,
+}
+
+/// The limits of an instance that matter when posting, from `limits`.
+#[derive(Debug, Clone)]
+pub struct InstanceLimits {
+    /// Limits of statuses. Mastodon's limits if the instance doesn't send
+    /// them.
+    pub statuses: StatusesConfiguration,
+    /// Limits of media attachments, if the instance sends them.
+    pub media_attachments: Option<MediaAttachmentsConfiguration>,
+    /// Limits of polls, if the instance sends them.
+    pub polls: Option<PollsConfiguration>,
+}
+
+impl From<InstanceConfiguration> for InstanceLimits {
+    fn from(configuration: InstanceConfiguration) -> Self {
+        InstanceLimits {
+            statuses: configuration.statuses.unwrap_or_default(),
+            media_attachments: configuration.media_attachments,
+            polls: configuration.polls,
+        }
+    }
+}
+
+impl From<Instance> for InstanceLimits {
+    fn from(instance: Instance) -> Self {
+        match instance.configuration {
+            Some(configuration) => configuration.into(),
+            None => InstanceLimits {
+                statuses: StatusesConfiguration {
+                    max_characters: instance.max_toot_chars.unwrap_or(500),
+                    ..StatusesConfiguration::default()
+                },
+                media_attachments: None,
+                polls: None,
+            },
+        }
+    }
 }
 
 /// Object containing url for streaming api.
@@ -37,7 +390,10 @@ pub struct StreamingApi {
 /// Statistics about the Mastodon instance.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Stats {
-    user_count: u64,
-    status_count: u64,
-    domain_count: u64,
+    /// The number of users registered on the instance.
+    pub user_count: u64,
+    /// The number of statuses posted on the instance.
+    pub status_count: u64,
+    /// The number of other instances known to the instance.
+    pub domain_count: u64,
 }
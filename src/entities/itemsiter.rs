@@ -15,6 +15,7 @@ use crate::page::Page;
 /// #   client_secret: "".into(),
 /// #   redirect: "".into(),
 /// #   token: "".into(),
+/// #   token_kind: Default::default(),
 /// # };
 /// let client = Mastodon::from_data(data);
 /// let statuses = client.statuses("user-id", None)?;
@@ -34,7 +35,7 @@ pub(crate) struct ItemsIter<'a, T: Clone + for<'de> Deserialize<'de>> {
 impl<'a, T: Clone + for<'de> Deserialize<'de>> ItemsIter<'a, T> {
     pub(crate) fn new(page: Page<'a, T>) -> ItemsIter<'a, T> {
         ItemsIter {
-            page: page,
+            page,
             buffer: vec![],
             cur_idx: 0,
             use_initial: true,
@@ -79,9 +80,7 @@ impl<'a, T: Clone + for<'de> Deserialize<'de>> Iterator for ItemsIter<'a, T> {
             Some(self.page.initial_items[idx].clone())
         } else {
             if self.need_next_page() {
-                if self.fill_next_page().is_none() {
-                    return None;
-                }
+                self.fill_next_page()?;
             }
             let idx = self.cur_idx;
             self.cur_idx += 1;
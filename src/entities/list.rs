@@ -1,5 +1,42 @@
+//! Module containing everything related to lists.
+
+/// A list of accounts curated by the user.
+///
+/// `replies_policy` and `exclusive` are missing from older instances, where
+/// they default to `None` and `false`.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::list::List;
+///
+/// let list: List = serde_json::from_str(r#"{"id": "12249", "title": "Friends"}"#).unwrap();
+/// assert!(list.replies_policy.is_none());
+/// assert!(!list.exclusive);
+/// ```
 #[derive(Clone, Debug, Deserialize)]
 pub struct List {
-    id: String,
-    title: String,
+    /// The ID of the list.
+    pub id: String,
+    /// The user-defined title of the list.
+    pub title: String,
+    /// Which replies should be shown in the list.
+    pub replies_policy: Option<RepliesPolicy>,
+    /// Whether members of the list are hidden from the home timeline.
+    #[serde(default)]
+    pub exclusive: bool,
+}
+
+/// Which replies should be shown in a list.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum RepliesPolicy {
+    /// Show replies to any followed user.
+    #[serde(rename = "followed")]
+    Followed,
+    /// Show replies to members of the list.
+    #[serde(rename = "list")]
+    List,
+    /// Show replies to no one.
+    #[serde(rename = "none")]
+    None,
 }
@@ -0,0 +1,23 @@
+//! Module containing everything related to read position markers.
+
+use chrono::prelude::*;
+
+/// The markers of the timelines requested with `get_markers`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Markers {
+    /// The marker of the home timeline, if requested and set.
+    pub home: Option<Marker>,
+    /// The marker of the notifications, if requested and set.
+    pub notifications: Option<Marker>,
+}
+
+/// The user's read position in a timeline, shared between their clients.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Marker {
+    /// The ID of the most recently read item in the timeline.
+    pub last_read_id: String,
+    /// Incremented each time the marker is updated.
+    pub version: u64,
+    /// The time the marker was last updated.
+    pub updated_at: DateTime<Utc>,
+}
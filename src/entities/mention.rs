@@ -1,3 +1,6 @@
+//! Module containing the mention of another user.
+
+/// A mention of another user.
 pub struct Mention {
     /// URL of user's profile (can be remote)
     pub url: String,
@@ -1,35 +1,128 @@
 pub mod account;
+pub mod announcement;
 pub mod attachment;
 pub mod card;
 pub mod context;
+pub mod featured_tag;
+pub mod filter;
 pub mod instance;
 pub(crate) mod itemsiter;
 pub mod list;
+pub mod marker;
 pub mod mention;
 pub mod notification;
+#[cfg(feature = "pleroma")]
+pub mod pleroma;
 pub mod relationship;
 pub mod report;
 pub mod search_result;
 pub mod status;
+pub mod trends;
 
 /// An empty JSON object.
 #[derive(Deserialize)]
 pub struct Empty {}
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    String(String),
+    Number(u64),
+}
+
+impl StringOrNumber {
+    fn parse<E: serde::de::Error>(self) -> Result<u64, E> {
+        match self {
+            StringOrNumber::String(s) => s.parse().map_err(E::custom),
+            StringOrNumber::Number(n) => Ok(n),
+        }
+    }
+}
+
+// Counts that newer instances send as strings, such as `statuses_count` on
+// featured tags and the numbers in hashtag history.
+pub(crate) fn string_or_number<'de, D>(val: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    StringOrNumber::deserialize(val)?.parse()
+}
+
+// Dates such as `last_status_at`, which instances older than 3.1 send as a
+// full timestamp.
+pub(crate) fn date_or_time<'de, D>(val: D) -> Result<Option<chrono::NaiveDate>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    let date = match Option::<String>::deserialize(val)? {
+        Some(date) => date,
+        None => return Ok(None),
+    };
+
+    chrono::NaiveDate::parse_from_str(date.get(..10).unwrap_or(&date), "%Y-%m-%d")
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+// Lists that instances send as `null` rather than empty, such as a report's
+// `rule_ids`. Use with `#[serde(default)]` for lists that may also be missing.
+pub(crate) fn null_as_empty<'de, D, T>(val: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    use serde::Deserialize;
+
+    Ok(Option::<Vec<T>>::deserialize(val)?.unwrap_or_default())
+}
+
+// `string_or_number` for fields that may be missing or `null`.
+pub(crate) fn optional_string_or_number<'de, D>(val: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    Option::<StringOrNumber>::deserialize(val)?
+        .map(StringOrNumber::parse)
+        .transpose()
+}
+
 pub mod prelude {
     //! The purpose of this module is to alleviate imports of many common structs
     //! by adding a glob import to the top of mastodon heavy modules:
-    pub use super::account::{Account, CredientialsBuilder, Source};
+    pub use super::account::{
+        Account, CredientialsBuilder, FamiliarFollowers, MetadataField, Source,
+    };
+    pub use super::announcement::{Announcement, AnnouncementReaction, AnnouncementStatus};
     pub use super::attachment::{Attachment, MediaType};
     pub use super::card::Card;
-    pub use super::context::Context;
+    pub use super::context::{Context, ReplyTree};
+    pub use super::featured_tag::FeaturedTag;
+    pub use super::filter::{
+        Filter, FilterAction, FilterContext, FilterKeyword, FilterKeywordAttributes, FilterResult,
+        FilterStatus, FilterV2,
+    };
     pub use super::instance::*;
-    pub use super::list::List;
+    pub use super::list::{List, RepliesPolicy};
+    pub use super::marker::{Marker, Markers};
     pub use super::mention::Mention;
-    pub use super::notification::Notification;
+    pub use super::notification::{
+        GroupedNotificationsResults, Notification, NotificationGroup, NotificationPolicy,
+        NotificationRequest, NotificationType, PartialAccountWithAvatar,
+    };
+    #[cfg(feature = "pleroma")]
+    pub use super::pleroma::{Chat, ChatMessage};
     pub use super::relationship::Relationship;
     pub use super::report::Report;
-    pub use super::search_result::SearchResult;
-    pub use super::status::{Application, Emoji, Status};
+    pub use super::search_result::{SearchResult, SearchResultV2};
+    pub use super::status::{
+        Application, Emoji, Status, StatusEdit, StatusSource, Tag, TagHistory,
+    };
+    pub use super::trends::TrendsLink;
     pub use super::Empty;
 }
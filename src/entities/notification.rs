@@ -3,6 +3,7 @@
 use super::account::Account;
 use super::status::Status;
 use chrono::prelude::*;
+use serde::{Deserialize, Deserializer};
 
 /// A struct containing info about a notification.
 #[derive(Debug, Clone, Deserialize)]
@@ -20,19 +21,341 @@ pub struct Notification {
     pub status: Option<Status>,
 }
 
+impl Notification {
+    /// The status the notification is about, for the types that have one.
+    pub fn target_status(&self) -> Option<&Status> {
+        if self.notification_type.has_status() {
+            self.status.as_ref()
+        } else {
+            None
+        }
+    }
+}
+
 /// The type of notification.
-#[derive(Debug, Clone, Deserialize)]
+///
+/// Types this crate doesn't know about yet are deserialised as `Unknown`
+/// with the type the server sent, instead of failing.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::notification::NotificationType;
+///
+/// let kind: NotificationType = serde_json::from_str(r#""admin.sign_up""#).unwrap();
+/// assert_eq!(kind, NotificationType::AdminSignUp);
+///
+/// let kind: NotificationType = serde_json::from_str(r#""quote""#).unwrap();
+/// assert_eq!(kind, NotificationType::Unknown("quote".to_owned()));
+/// assert!(!kind.has_status());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NotificationType {
     /// Someone mentioned the application client in another status.
-    #[serde(rename = "mention")]
     Mention,
     /// Someone reblogged one of the application client's statuses.
-    #[serde(rename = "reblog")]
     Reblog,
     /// Someone favourited one of the application client's statuses.
-    #[serde(rename = "favourite")]
     Favourite,
     /// Someone followed the application client.
-    #[serde(rename = "follow")]
     Follow,
+    /// Someone requested to follow the application client.
+    FollowRequest,
+    /// Someone the application client subscribed to posted a status.
+    Status,
+    /// A poll the application client voted in or created has ended.
+    Poll,
+    /// A status the application client reblogged was edited.
+    Update,
+    /// Someone signed up to the instance, sent to moderators.
+    AdminSignUp,
+    /// A new report was filed, sent to moderators.
+    AdminReport,
+    /// A type unknown to this crate.
+    Unknown(String),
+}
+
+impl NotificationType {
+    /// Whether notifications of this type are about a status, and so have
+    /// `Notification::status` set.
+    pub fn has_status(&self) -> bool {
+        match *self {
+            NotificationType::Mention
+            | NotificationType::Reblog
+            | NotificationType::Favourite
+            | NotificationType::Status
+            | NotificationType::Poll
+            | NotificationType::Update => true,
+            NotificationType::Follow
+            | NotificationType::FollowRequest
+            | NotificationType::AdminSignUp
+            | NotificationType::AdminReport
+            | NotificationType::Unknown(_) => false,
+        }
+    }
+
+    /// The type as the server names it, such as `"admin.sign_up"`.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            NotificationType::Mention => "mention",
+            NotificationType::Reblog => "reblog",
+            NotificationType::Favourite => "favourite",
+            NotificationType::Follow => "follow",
+            NotificationType::FollowRequest => "follow_request",
+            NotificationType::Status => "status",
+            NotificationType::Poll => "poll",
+            NotificationType::Update => "update",
+            NotificationType::AdminSignUp => "admin.sign_up",
+            NotificationType::AdminReport => "admin.report",
+            NotificationType::Unknown(ref kind) => kind,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NotificationType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let kind = String::deserialize(deserializer)?;
+
+        Ok(match &*kind {
+            "mention" => NotificationType::Mention,
+            "reblog" => NotificationType::Reblog,
+            "favourite" => NotificationType::Favourite,
+            "follow" => NotificationType::Follow,
+            "follow_request" => NotificationType::FollowRequest,
+            "status" => NotificationType::Status,
+            "poll" => NotificationType::Poll,
+            "update" => NotificationType::Update,
+            "admin.sign_up" => NotificationType::AdminSignUp,
+            "admin.report" => NotificationType::AdminReport,
+            _ => NotificationType::Unknown(kind),
+        })
+    }
+}
+
+/// Notifications grouped together by the v2 notifications API, along with
+/// the accounts and statuses they refer to.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GroupedNotificationsResults {
+    /// Accounts referenced by the groups, in full.
+    #[serde(default)]
+    pub accounts: Vec<Account>,
+    /// Accounts referenced by the groups, when only partial accounts were
+    /// requested with `ExpandAccounts::PartialAvatars`.
+    #[serde(default)]
+    pub partial_accounts: Vec<PartialAccountWithAvatar>,
+    /// Statuses referenced by the groups.
+    #[serde(default)]
+    pub statuses: Vec<Status>,
+    /// The groups of notifications.
+    pub notification_groups: Vec<NotificationGroup>,
+}
+
+impl GroupedNotificationsResults {
+    /// The groups, with the accounts and status they refer to looked up from
+    /// `accounts`, `partial_accounts` and `statuses`.
+    pub fn hydrated_groups(&self) -> Vec<HydratedNotificationGroup<'_>> {
+        self.notification_groups
+            .iter()
+            .map(|group| HydratedNotificationGroup {
+                group,
+                sample_accounts: group
+                    .sample_account_ids
+                    .iter()
+                    .filter_map(|id| self.find_account(id))
+                    .collect(),
+                status: group
+                    .status_id
+                    .as_ref()
+                    .and_then(|id| self.statuses.iter().find(|status| status.id == *id)),
+            })
+            .collect()
+    }
+
+    fn find_account(&self, id: &str) -> Option<GroupAccount<'_>> {
+        if let Some(account) = self.accounts.iter().find(|account| account.id == id) {
+            return Some(GroupAccount::Full(account));
+        }
+
+        self.partial_accounts
+            .iter()
+            .find(|account| account.id == id)
+            .map(GroupAccount::Partial)
+    }
+}
+
+/// A group of notifications of the same type, such as everyone that
+/// favourited a status.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationGroup {
+    /// The key identifying the group. Notifications that weren't grouped
+    /// have a key starting with `ungrouped-`.
+    pub group_key: String,
+    /// The number of notifications in the group.
+    pub notifications_count: u64,
+    /// The type of the notifications in the group.
+    #[serde(rename = "type")]
+    pub notification_type: NotificationType,
+    /// The ID of the most recent notification in the group.
+    pub most_recent_notification_id: String,
+    /// The ID of the oldest notification of the group in this page.
+    pub page_min_id: Option<String>,
+    /// The ID of the newest notification of the group in this page.
+    pub page_max_id: Option<String>,
+    /// The time of the newest notification of the group in this page.
+    pub latest_page_notification_at: Option<DateTime<Utc>>,
+    /// The IDs of some of the accounts that caused the notifications, most
+    /// recent first.
+    pub sample_account_ids: Vec<String>,
+    /// The ID of the status the notifications are about, if any.
+    pub status_id: Option<String>,
+}
+
+/// The parts of an account needed to show its avatar in a notification
+/// group.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PartialAccountWithAvatar {
+    /// The ID of the account.
+    pub id: String,
+    /// Equals `username` for local users, includes `@domain` for remote ones.
+    pub acct: String,
+    /// URL of the user's profile page (can be remote).
+    pub url: String,
+    /// URL to the avatar image.
+    pub avatar: String,
+    /// URL to the avatar static image (gif).
+    pub avatar_static: String,
+    /// Whether the account must approve follow requests.
+    pub locked: bool,
+    /// Whether the account is automated.
+    pub bot: bool,
+}
+
+/// A notification group with the accounts and status it refers to.
+#[derive(Debug, Clone)]
+pub struct HydratedNotificationGroup<'a> {
+    /// The group itself.
+    pub group: &'a NotificationGroup,
+    /// The accounts of `sample_account_ids` that were in the results.
+    pub sample_accounts: Vec<GroupAccount<'a>>,
+    /// The status of `status_id`, if it was in the results.
+    pub status: Option<&'a Status>,
+}
+
+/// An account referenced by a notification group, in full or partially.
+#[derive(Debug, Clone, Copy)]
+pub enum GroupAccount<'a> {
+    /// A full account.
+    Full(&'a Account),
+    /// A partial account.
+    Partial(&'a PartialAccountWithAvatar),
+}
+
+impl GroupAccount<'_> {
+    /// The ID of the account.
+    pub fn id(&self) -> &str {
+        match *self {
+            GroupAccount::Full(account) => &account.id,
+            GroupAccount::Partial(account) => &account.id,
+        }
+    }
+
+    /// URL to the avatar image of the account.
+    pub fn avatar(&self) -> &str {
+        match *self {
+            GroupAccount::Full(account) => &account.avatar,
+            GroupAccount::Partial(account) => &account.avatar,
+        }
+    }
+}
+
+impl From<Vec<Notification>> for GroupedNotificationsResults {
+    /// Put each notification from the v1 API in a group of its own.
+    fn from(notifications: Vec<Notification>) -> Self {
+        let mut results = GroupedNotificationsResults {
+            accounts: Vec::new(),
+            partial_accounts: Vec::new(),
+            statuses: Vec::new(),
+            notification_groups: Vec::new(),
+        };
+
+        for notification in notifications {
+            let Notification {
+                id,
+                notification_type,
+                created_at,
+                account,
+                status,
+            } = notification;
+
+            results.notification_groups.push(NotificationGroup {
+                group_key: format!("ungrouped-{}", id),
+                notifications_count: 1,
+                notification_type,
+                most_recent_notification_id: id.clone(),
+                page_min_id: Some(id.clone()),
+                page_max_id: Some(id),
+                latest_page_notification_at: Some(created_at),
+                sample_account_ids: vec![account.id.clone()],
+                status_id: status.as_ref().map(|status| status.id.clone()),
+            });
+
+            if !results.accounts.iter().any(|known| known.id == account.id) {
+                results.accounts.push(account);
+            }
+
+            if let Some(status) = status {
+                if !results.statuses.iter().any(|known| known.id == status.id) {
+                    results.statuses.push(status);
+                }
+            }
+        }
+
+        results
+    }
+}
+
+/// Notifications from an account that were held back by the notification
+/// policy, waiting to be accepted or dismissed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationRequest {
+    /// The ID of the request.
+    pub id: String,
+    /// The time the request was created.
+    pub created_at: DateTime<Utc>,
+    /// The time the request was last updated.
+    pub updated_at: DateTime<Utc>,
+    /// The account that caused the notifications.
+    pub account: Account,
+    /// The number of notifications held back. Sent as a string, as it may be
+    /// capped by the server.
+    pub notifications_count: String,
+    /// The most recent status of the notifications, if any.
+    pub last_status: Option<Status>,
+}
+
+/// Which notifications the server holds back as notification requests.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationPolicy {
+    /// Whether notifications from accounts the user doesn't follow are held
+    /// back.
+    pub filter_not_following: bool,
+    /// Whether notifications from accounts that don't follow the user are
+    /// held back.
+    pub filter_not_followers: bool,
+    /// Whether notifications from accounts created in the last 30 days are
+    /// held back.
+    pub filter_new_accounts: bool,
+    /// Whether private mentions the user didn't reply to are held back.
+    pub filter_private_mentions: bool,
+    /// How many notifications are being held back.
+    pub summary: NotificationPolicySummary,
+}
+
+/// How many notifications are being held back by the notification policy.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationPolicySummary {
+    /// The number of notification requests waiting.
+    pub pending_requests_count: u64,
+    /// The number of notifications held back in all requests.
+    pub pending_notifications_count: u64,
 }
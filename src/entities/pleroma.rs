@@ -0,0 +1,68 @@
+//! Module containing the entities of Pleroma and Akkoma's extensions to the
+//! Mastodon API. Only available with the `pleroma` feature.
+
+use super::account::Account;
+use super::attachment::Attachment;
+use super::card::Card;
+use super::status::Emoji;
+use chrono::prelude::*;
+
+/// A chat with another account.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Chat {
+    /// The ID of the chat.
+    pub id: String,
+    /// The account the chat is with.
+    pub account: Account,
+    /// The number of unread messages.
+    pub unread: u64,
+    /// The most recent message of the chat, if any.
+    pub last_message: Option<ChatMessage>,
+    /// The time of the most recent activity in the chat.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A message in a chat.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::pleroma::ChatMessage;
+///
+/// let message: ChatMessage = serde_json::from_str(r#"{
+///     "id": "9uXpVfbjvn3iaYf1Ue",
+///     "chat_id": "9uXpUe3RbDELQzFhaq",
+///     "account_id": "9tvr3Tgz4pOIBjWZSi",
+///     "content": "Hello!",
+///     "created_at": "2020-06-11T11:47:42.000Z",
+///     "emojis": [],
+///     "attachment": null,
+///     "card": null,
+///     "unread": false
+/// }"#).unwrap();
+/// assert_eq!(message.content.unwrap(), "Hello!");
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatMessage {
+    /// The ID of the message.
+    pub id: String,
+    /// The ID of the chat the message is in.
+    pub chat_id: String,
+    /// The ID of the account that sent the message.
+    pub account_id: String,
+    /// The text of the message, in HTML. Missing if the message is only an
+    /// attachment.
+    pub content: Option<String>,
+    /// The time the message was sent.
+    pub created_at: DateTime<Utc>,
+    /// The custom emojis used in the message.
+    #[serde(default)]
+    pub emojis: Vec<Emoji>,
+    /// The attachment of the message, if any.
+    pub attachment: Option<Attachment>,
+    /// A preview of the first link in the message, if any.
+    pub card: Option<Card>,
+    /// Whether the user hasn't read the message.
+    #[serde(default)]
+    pub unread: bool,
+}
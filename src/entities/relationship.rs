@@ -12,7 +12,7 @@ pub struct Relationship {
     pub followed_by: bool,
     /// Whether the application client blocks the account.
     pub blocking: bool,
-    /// Whether the application client blocks the account.
+    /// Whether the application client mutes the account.
     pub muting: bool,
     /// Whether the application client has requested to follow the account.
     pub requested: bool,
@@ -21,3 +21,11 @@ pub struct Relationship {
     /// Whether the user is currently blocking the accounts's domain
     pub domain_blocking: bool,
 }
+
+impl Relationship {
+    /// Whether statuses of the account should be hidden, because the user
+    /// blocks or mutes it or blocks its domain.
+    pub fn hides_statuses(&self) -> bool {
+        self.blocking || self.muting || self.domain_blocking
+    }
+}
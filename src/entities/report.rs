@@ -1,10 +1,52 @@
 //! module containing information about a finished report of a user.
 
+use super::account::Account;
+use crate::report_builder::ReportCategory;
+use chrono::prelude::*;
+
 /// A struct containing info about a report.
+///
+/// `status_ids` and `rule_ids` are empty when the instance sends `null`, as
+/// Mastodon does for reports that aren't rule violations.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::report::Report;
+/// use mammut::report_builder::ReportCategory;
+///
+/// let report: Report = serde_json::from_str(r#"{
+///     "id": "48914",
+///     "action_taken": false,
+///     "category": "impersonation",
+///     "status_ids": null,
+///     "rule_ids": null
+/// }"#).unwrap();
+/// assert_eq!(report.category, Some(ReportCategory::Unknown));
+/// assert!(report.rule_ids.is_empty());
+/// ```
 #[derive(Debug, Clone, Deserialize)]
 pub struct Report {
     /// The ID of the report.
     pub id: String,
-    /// The action taken in response to the report.
-    pub action_taken: String,
+    /// Whether a moderator has acted on the report.
+    pub action_taken: bool,
+    /// The time a moderator acted on the report, if one has.
+    pub action_taken_at: Option<DateTime<Utc>>,
+    /// The kind of problem reported. Missing on instances older than 3.5.
+    pub category: Option<ReportCategory>,
+    /// The reason for the report.
+    pub comment: Option<String>,
+    /// Whether the report was sent to the reported account's instance.
+    pub forwarded: Option<bool>,
+    /// The time the report was made.
+    pub created_at: Option<DateTime<Utc>>,
+    /// The IDs of the statuses attached to the report.
+    #[serde(default, deserialize_with = "super::null_as_empty")]
+    pub status_ids: Vec<String>,
+    /// The IDs of the rules that were broken.
+    #[serde(default, deserialize_with = "super::null_as_empty")]
+    pub rule_ids: Vec<String>,
+    /// The reported account.
+    pub target_account: Option<Account>,
 }
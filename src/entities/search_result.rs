@@ -1,6 +1,6 @@
 //! A module containing info relating to a search result.
 
-use super::prelude::{Account, Status};
+use super::prelude::{Account, Status, Tag};
 
 /// A struct containing results of a search.
 #[derive(Debug, Clone, Deserialize)]
@@ -12,3 +12,14 @@ pub struct SearchResult {
     /// An array of matched hashtags, as strings.
     pub hashtags: Vec<String>,
 }
+
+/// A struct containing results of a search with `search_v2`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchResultV2 {
+    /// An array of matched Accounts.
+    pub accounts: Vec<Account>,
+    /// An array of matched Statuses.
+    pub statuses: Vec<Status>,
+    /// An array of matched hashtags.
+    pub hashtags: Vec<Tag>,
+}
@@ -21,23 +21,33 @@ pub struct Status {
     /// The ID of the account this status is replying to, if the status is
     /// a reply.
     pub in_reply_to_account_id: Option<String>,
-    /// If this status is a reblogged Status of another User.
+    /// If this status is a reblog, the Status that was reblogged. This
+    /// status then has no content of its own.
     pub reblog: Option<Box<Status>>,
     /// Body of the status; this will contain HTML
     /// (remote HTML already sanitized)
     pub content: String,
     /// The time the status was created.
     pub created_at: DateTime<Utc>,
+    /// The time the status was last edited, if it has been edited.
+    pub edited_at: Option<DateTime<Utc>>,
     /// An array of Emoji
     pub emojis: Vec<Emoji>,
     /// The number of reblogs for the status.
     pub reblogs_count: u64,
     /// The number of favourites for the status.
     pub favourites_count: u64,
+    /// The number of replies to the status. Always `0` on instances older
+    /// than 2.5, which don't send it.
+    #[serde(default)]
+    pub replies_count: u64,
     /// Whether the application client has reblogged the status.
     pub reblogged: Option<bool>,
     /// Whether the application client has favourited the status.
     pub favourited: Option<bool>,
+    /// Whether the application client has muted the conversation of the
+    /// status.
+    pub muted: Option<bool>,
     /// Whether media attachments should be hidden by default.
     pub sensitive: bool,
     /// If not empty, warning text that should be displayed before the actual
@@ -47,16 +57,55 @@ pub struct Status {
     pub visibility: Visibility,
     /// An array of attachments.
     pub media_attachments: Vec<Attachment>,
-    /// An array of mentions.
+    /// An array of mentions. Empty if the server didn't send any.
+    #[serde(default)]
     pub mentions: Vec<Mention>,
-    /// An array of tags.
+    /// An array of tags. Empty if the server didn't send any.
+    #[serde(default)]
     pub tags: Vec<Tag>,
-    /// Name of application used to post status.
+    /// The application used to post the status. Missing on reblogs and on
+    /// most statuses from other instances.
     pub application: Option<Application>,
     /// The detected language for the status, if detected.
     pub language: Option<String>,
     /// Whether this is the pinned status for the account that posted it.
     pub pinned: Option<bool>,
+    /// The filters of the user that matched the status. Missing on instances
+    /// older than 4.0, which remove filtered statuses instead.
+    #[serde(default)]
+    pub filtered: Vec<FilterResult>,
+}
+
+/// The plain text source of a status, used to edit it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusSource {
+    /// The ID of the status.
+    pub id: String,
+    /// The plain text used to compose the status.
+    pub text: String,
+    /// The plain text used to compose the status's warning text.
+    pub spoiler_text: String,
+}
+
+/// A revision of a status, from before or after it was edited.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusEdit {
+    /// Body of the status at this revision; this will contain HTML.
+    pub content: String,
+    /// The warning text of the status at this revision.
+    pub spoiler_text: String,
+    /// Whether the status was marked sensitive at this revision.
+    pub sensitive: bool,
+    /// The time of the revision.
+    pub created_at: DateTime<Utc>,
+    /// The account that posted the status.
+    pub account: Account,
+    /// The attachments of the status at this revision.
+    #[serde(default)]
+    pub media_attachments: Vec<Attachment>,
+    /// The emojis used at this revision.
+    #[serde(default)]
+    pub emojis: Vec<Emoji>,
 }
 
 /// A mention of another user.
@@ -73,7 +122,7 @@ pub struct Mention {
 }
 
 /// Struct representing an emoji within text.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct Emoji {
     /// The shortcode of the emoji
     pub shortcode: String,
@@ -81,6 +130,110 @@ pub struct Emoji {
     pub static_url: String,
     /// URL to the emoji image
     pub url: String,
+    /// Whether the emoji is shown in the instance's emoji picker. Only sent
+    /// by `custom_emojis`.
+    #[serde(default)]
+    pub visible_in_picker: bool,
+    /// The category the emoji is shown under in the picker, if any.
+    pub category: Option<String>,
+}
+
+/// A piece of text split by `expand_emojis`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextOrEmoji<'a> {
+    /// Plain text.
+    Text(&'a str),
+    /// A custom emoji used in the text as `:shortcode:`.
+    Emoji(&'a Emoji),
+}
+
+/// Split `text` into plain text and the custom emojis from `emojis` it uses,
+/// such as the content of a status and `Status::emojis`, so the emojis can be
+/// shown as images.
+///
+/// Like Mastodon, a `:shortcode:` only counts as an emoji if it isn't
+/// directly next to a letter, digit or another colon.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::status::{expand_emojis, Emoji, TextOrEmoji};
+///
+/// let blobcat: Emoji = serde_json::from_str(r#"{
+///     "shortcode": "blobcat",
+///     "url": "https://example.com/blobcat.png",
+///     "static_url": "https://example.com/blobcat.png"
+/// }"#).unwrap();
+/// let emojis = [blobcat.clone()];
+///
+/// assert_eq!(
+///     expand_emojis("hi :blobcat: :unknown:", &emojis),
+///     vec![
+///         TextOrEmoji::Text("hi "),
+///         TextOrEmoji::Emoji(&blobcat),
+///         TextOrEmoji::Text(" :unknown:"),
+///     ]
+/// );
+/// assert_eq!(
+///     expand_emojis(":blobcat::blobcat:", &emojis),
+///     vec![TextOrEmoji::Text(":blobcat::blobcat:")]
+/// );
+/// assert_eq!(
+///     expand_emojis("a:blobcat:", &emojis),
+///     vec![TextOrEmoji::Text("a:blobcat:")]
+/// );
+/// assert_eq!(
+///     expand_emojis("12:30 :blobcat:", &emojis),
+///     vec![TextOrEmoji::Text("12:30 "), TextOrEmoji::Emoji(&blobcat)]
+/// );
+/// assert!(expand_emojis("", &emojis).is_empty());
+/// ```
+pub fn expand_emojis<'a>(text: &'a str, emojis: &'a [Emoji]) -> Vec<TextOrEmoji<'a>> {
+    let is_boundary = |c: Option<char>| !c.map_or(false, |c| c.is_alphanumeric() || c == ':');
+    let mut pieces = Vec::new();
+    // The start of the text that hasn't been added to `pieces` yet.
+    let mut start = 0;
+    // Where to look for the next opening colon.
+    let mut from = 0;
+
+    while let Some(open) = text[from..].find(':').map(|i| from + i) {
+        let close = match text[open + 1..].find(':') {
+            Some(i) => open + 1 + i,
+            None => break,
+        };
+        let shortcode = &text[open + 1..close];
+
+        let valid = shortcode.len() >= 2
+            && shortcode
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && is_boundary(text[..open].chars().next_back())
+            && is_boundary(text[close + 1..].chars().next());
+        let emoji = if valid {
+            emojis.iter().find(|emoji| emoji.shortcode == shortcode)
+        } else {
+            None
+        };
+
+        match emoji {
+            Some(emoji) => {
+                if start < open {
+                    pieces.push(TextOrEmoji::Text(&text[start..open]));
+                }
+                pieces.push(TextOrEmoji::Emoji(emoji));
+                start = close + 1;
+                from = close + 1;
+            }
+            // The closing colon may open the next shortcode.
+            None => from = close,
+        }
+    }
+
+    if start < text.len() {
+        pieces.push(TextOrEmoji::Text(&text[start..]));
+    }
+
+    pieces
 }
 
 /// Hashtags in the status.
@@ -90,6 +243,44 @@ pub struct Tag {
     pub name: String,
     /// The URL of the hashtag.
     pub url: String,
+    /// Whether the user follows the hashtag. Only set when the hashtag was
+    /// fetched by itself on instances that support following hashtags.
+    pub following: Option<bool>,
+    /// Usage of the hashtag over the last week, most recent day first. Only
+    /// set when the hashtag was fetched by itself or from the trends.
+    #[serde(default)]
+    pub history: Vec<TagHistory>,
+}
+
+/// Usage of a hashtag or link on one day.
+///
+/// The server sends these numbers as strings, they are parsed into `u64`s.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::entities::status::TagHistory;
+///
+/// let history: TagHistory = serde_json::from_str(r#"{
+///     "day": "1574553600",
+///     "uses": "200",
+///     "accounts": "31"
+/// }"#).unwrap();
+/// assert_eq!(history.day, 1574553600);
+/// assert_eq!(history.uses, 200);
+/// assert_eq!(history.accounts, 31);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct TagHistory {
+    /// The day, as a UNIX timestamp of its midnight.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub day: u64,
+    /// The number of times it was used that day.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub uses: u64,
+    /// The number of accounts that used it that day.
+    #[serde(deserialize_with = "super::string_or_number")]
+    pub accounts: u64,
 }
 
 /// Application details.
@@ -99,4 +290,7 @@ pub struct Application {
     pub name: String,
     /// Homepage URL of the application.
     pub website: Option<String>,
+    /// The key to use with the push API. Only set by
+    /// `verify_app_credentials`.
+    pub vapid_key: Option<String>,
 }
@@ -0,0 +1,16 @@
+//! Module containing the links trending on an instance.
+
+use super::card::Card;
+use super::status::TagHistory;
+
+/// A link that is trending on the instance, from `trending_links`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrendsLink {
+    /// The preview card of the link.
+    #[serde(flatten)]
+    pub card: Card,
+    /// How much the link was shared over the last week, most recent day
+    /// first.
+    #[serde(default)]
+    pub history: Vec<TagHistory>,
+}
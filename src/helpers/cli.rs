@@ -0,0 +1,76 @@
+//! Helpers for command line apps, which ask the user for input on the
+//! terminal.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::apps::App;
+use crate::{AuthStatus, Data, Error, Mastodon, Registration, Result};
+
+/// Load the `Data` saved at `path` by an earlier run, or authorise the app
+/// on the instance at `base` if there is none.
+///
+/// To authorise, the app is registered, the user is asked to open the
+/// authorisation url and paste the code back on the terminal, and the new
+/// `Data` is saved to `path` with `Data::save_to_file`, so on unix only its
+/// owner can read it. Saved `Data` whose token the instance refuses, or that
+/// can't be read, such as a file from an older version, is replaced the same
+/// way.
+///
+/// ```no_run
+/// # extern crate mammut;
+/// # fn main() -> mammut::Result<()> {
+/// use mammut::apps::{App, Scopes};
+/// use mammut::helpers::cli;
+///
+/// let app = App::builder()
+///     .client_name("mammut-examples")
+///     .scopes(Scopes::READ)
+///     .build()?;
+/// let mastodon = cli::authenticate("https://mastodon.social", app, "mastodon-data.json")?;
+///
+/// println!("{:?}", mastodon.verify_credentials()?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn authenticate<P: AsRef<Path>>(base: &str, app: App, path: P) -> Result<Mastodon> {
+    let path = path.as_ref();
+
+    if path.exists() {
+        match Data::from_file(path) {
+            Ok(data) => {
+                let mastodon = Mastodon::from_data(data);
+
+                match mastodon.check_auth()? {
+                    AuthStatus::Invalid => {}
+                    _ => return Ok(mastodon),
+                }
+            }
+            Err(Error::InvalidData(_)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    let registered = Registration::new(base).app(app).build()?;
+    println!(
+        "Open this link to authorise the app: {}",
+        registered.authorize_url()
+    );
+    let code = read_line("Paste the code shown after authorising: ")?;
+
+    let mastodon = registered.complete(code.trim())?;
+    mastodon.data.save_to_file(path)?;
+
+    Ok(mastodon)
+}
+
+// Ask the user for a line of input.
+fn read_line(message: &str) -> Result<String> {
+    print!("{}", message);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    Ok(input)
+}
@@ -0,0 +1,66 @@
+//! Loading and saving `Data` as JSON.
+//!
+//! ```
+//! # extern crate mammut;
+//! # fn main() -> mammut::Result<()> {
+//! use mammut::helpers::json;
+//! use mammut::{Data, Error};
+//!
+//! let data = Data {
+//!     base: "https://mastodon.social".into(),
+//!     client_id: "id".into(),
+//!     client_secret: "secret".into(),
+//!     redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+//!     token: "token".into(),
+//!     token_kind: Default::default(),
+//! };
+//!
+//! let path = std::env::temp_dir().join("mammut-json-doctest/data.json");
+//! json::to_file(&data, &path)?;
+//! assert_eq!(json::from_file(&path)?, data);
+//!
+//! // Saved by a version that didn't have a field yet.
+//! match json::from_str(r#"{"base": "https://mastodon.social"}"#) {
+//!     Err(Error::InvalidData(reason)) => assert!(reason.contains("client_id")),
+//!     _ => panic!("missing fields should be rejected"),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+use super::invalid_json;
+use crate::{Data, Result};
+
+/// Load `Data` from the JSON file at `path`. The same as `Data::from_file`.
+pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Data> {
+    Data::from_file(path)
+}
+
+/// Save `Data` as JSON to `path`, creating its parent directories. On unix
+/// the file can only be read by its owner. The same as `Data::save_to_file`.
+pub fn to_file<P: AsRef<Path>>(data: &Data, path: P) -> Result<()> {
+    data.save_to_file(path)
+}
+
+/// Parse `Data` from JSON.
+pub fn from_str(s: &str) -> Result<Data> {
+    json::from_str(s).map_err(invalid_json)
+}
+
+/// Write `Data` as JSON to a string.
+pub fn to_string(data: &Data) -> Result<String> {
+    Ok(json::to_string_pretty(data)?)
+}
+
+/// Read `Data` as JSON from `reader`.
+pub fn from_reader<R: Read>(reader: R) -> Result<Data> {
+    json::from_reader(reader).map_err(invalid_json)
+}
+
+/// Write `Data` as JSON to `writer`.
+pub fn to_writer<W: Write>(data: &Data, writer: W) -> Result<()> {
+    Ok(json::to_writer_pretty(writer, data)?)
+}
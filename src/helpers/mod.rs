@@ -0,0 +1,50 @@
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "toml")]
+pub mod toml;
+
+use std::fs::{self, File};
+use std::path::Path;
+
+use crate::Error;
+
+// Failing to read saved JSON is an `Error::Io`, and failing to parse it an
+// `Error::InvalidData`.
+pub(crate) fn invalid_json(error: ::json::Error) -> Error {
+    if error.is_io() {
+        Error::Io(error.into())
+    } else {
+        Error::InvalidData(error.to_string())
+    }
+}
+
+// Create the file at `path` to save `Data` in, along with its parent
+// directories. On unix only the owner can read it, as it contains the access
+// token.
+pub(crate) fn create_private(path: &Path) -> crate::Result<File> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        options.mode(0o600);
+        let file = options.open(path)?;
+        // The mode is only used when creating the file.
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        Ok(file)
+    }
+
+    #[cfg(not(unix))]
+    Ok(options.open(path)?)
+}
@@ -0,0 +1,74 @@
+//! Loading and saving `Data` as TOML.
+//!
+//! ```
+//! # extern crate mammut;
+//! # fn main() -> mammut::Result<()> {
+//! use mammut::helpers::toml;
+//! use mammut::{Data, Error};
+//!
+//! let data = Data {
+//!     base: "https://mastodon.social".into(),
+//!     client_id: "id".into(),
+//!     client_secret: "secret".into(),
+//!     redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+//!     token: "token".into(),
+//!     token_kind: Default::default(),
+//! };
+//!
+//! let path = std::env::temp_dir().join("mammut-toml-doctest/data.toml");
+//! toml::to_file(&data, &path)?;
+//! assert_eq!(toml::from_file(&path)?, data);
+//!
+//! // Saved by a version that didn't have a field yet.
+//! match toml::from_str(r#"base = "https://mastodon.social""#) {
+//!     Err(Error::InvalidData(reason)) => assert!(reason.contains("client_id")),
+//!     _ => panic!("missing fields should be rejected"),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::{Data, Error, Result};
+
+/// Load `Data` from the TOML file at `path`.
+pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Data> {
+    from_str(&fs::read_to_string(path)?)
+}
+
+/// Save `Data` as TOML to `path`, creating its parent directories. On unix
+/// the file can only be read by its owner.
+pub fn to_file<P: AsRef<Path>>(data: &Data, path: P) -> Result<()> {
+    to_writer(data, super::create_private(path.as_ref())?)
+}
+
+/// Parse `Data` from TOML.
+pub fn from_str(s: &str) -> Result<Data> {
+    ::toml::from_str(s).map_err(|e| Error::InvalidData(e.to_string()))
+}
+
+/// Write `Data` as TOML to a string.
+///
+/// # Errors
+/// If `Data` can't be written as TOML, an `Error::SaveData` is returned.
+pub fn to_string(data: &Data) -> Result<String> {
+    ::toml::to_string(data).map_err(|e| Error::SaveData(e.to_string()))
+}
+
+/// Read `Data` as TOML from `reader`.
+pub fn from_reader<R: Read>(mut reader: R) -> Result<Data> {
+    let mut s = String::new();
+    reader.read_to_string(&mut s)?;
+
+    from_str(&s)
+}
+
+/// Write `Data` as TOML to `writer`.
+pub fn to_writer<W: Write>(data: &Data, mut writer: W) -> Result<()> {
+    writer.write_all(to_string(data)?.as_bytes())?;
+
+    Ok(())
+}
@@ -7,24 +7,19 @@
 //! # extern crate mammut;
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! use mammut::Registration;
-//! use mammut::apps::{AppBuilder, Scopes};
+//! use mammut::apps::Scopes;
 //!
-//! let app = AppBuilder {
-//!     client_name: "mammut_test",
-//!     redirect_uris: "urn:ietf:wg:oauth:2.0:oob",
-//!     scopes: Scopes::Read,
-//!     website: None,
-//! };
-//!
-//! let mut registration = Registration::new("https://mastodon.social");
-//! registration.register(app)?;
-//! let url = registration.authorise()?;
+//! let registered = Registration::new("https://mastodon.social")
+//!     .client_name("mammut_test")
+//!     .scopes(Scopes::READ)
+//!     .build()?;
+//! let url = registered.authorize_url();
 //! // Here you now need to open the url in the browser
 //! // And handle a the redirect url coming back with the code.
-//! let code = String::from("RETURNED_FROM_BROWSER");
-//! let mastodon = registration.create_access_token(code)?;
+//! let code = "RETURNED_FROM_BROWSER";
+//! let mastodon = registered.complete(code)?;
 //!
-//! println!("{:?}", mastodon.get_home_timeline()?.initial_items);
+//! println!("{:?}", mastodon.get_home_timeline(None)?.initial_items);
 //! # Ok(())
 //! # }
 //! ```
@@ -43,36 +38,56 @@ extern crate serde_json as json;
 pub mod apps;
 /// Entities returned from the API
 pub mod entities;
+/// Helpers for common tasks, such as authenticating command line apps.
+pub mod helpers;
 /// Constructing media attachments for a status.
 pub mod media_builder;
 /// Handling multiple pages of entities.
 pub mod page;
+/// The instance's rate limit.
+pub mod rate_limit;
 /// Registering your app.
 pub mod registration;
+/// Constructing a report
+pub mod report_builder;
 /// Constructing a status
 pub mod status_builder;
 
 use std::borrow::Cow;
+use std::env::{self, VarError};
 use std::error::Error as StdError;
 use std::fmt;
+use std::fs::File;
 use std::io::Error as IoError;
+use std::io::{BufReader, BufWriter, Write};
 use std::ops;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Utc};
 use hyperx::Error as HyperxError;
 use json::Error as SerdeError;
 use log::debug;
+use reqwest::header::InvalidHeaderValue;
 use reqwest::header::ToStrError as HeaderToStrError;
 use reqwest::header::{self, HeaderMap, HeaderValue};
 use reqwest::Error as HttpError;
-use reqwest::{Client, Response, StatusCode};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
 use url::ParseError as UrlError;
+use url::Url;
 
 use entities::prelude::*;
-pub use media_builder::MediaBuilder;
+pub use media_builder::{MediaBuilder, Thumbnail};
 use page::Page;
+pub use rate_limit::RateLimit;
 pub use status_builder::StatusBuilder;
+use status_builder::Visibility;
 
-pub use registration::Registration;
+pub use registration::{Authorization, AuthorizeRequest, Registered, Registration};
+pub use report_builder::{ReportBuilder, ReportCategory};
 /// Convience type over `std::result::Result` with `Error` as the error type.
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -86,7 +101,7 @@ macro_rules! methods {
                     .headers(self.headers.clone());
                 debug!("REQUEST: {:?}", request);
 
-                let response = request.send()?;
+                let response = self.send(request)?;
                 debug!("RESPONSE: {:?}", response);
 
                 deserialise(response)
@@ -97,17 +112,19 @@ macro_rules! methods {
 
 macro_rules! paged_routes {
 
-    (($method:ident) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
+    (($method:ident) $name:ident: $url:expr => [$ret:ty; $max:ident], $($rest:tt)*) => {
         doc_comment! {
             concat!(
                 "Equivalent to `/api/v1/",
                 $url,
-                "`\n# Errors\nIf `access_token` is not set."),
-            pub fn $name(&self) -> Result<Page<$ret>> {
-                let url = self.route(concat!("/api/v1/", $url));
-                let response = self.client.$method(&url)
-                    .headers(self.headers.clone())
-                    .send()?;
+                "`\n\nAt most `limit` items are returned per page, clamped to `",
+                stringify!($max),
+                "`.\n# Errors\nIf `access_token` is not set."),
+            pub fn $name(&self, limit: Option<u32>) -> Result<Page<'_, $ret>> {
+                let url = with_limit(self.route(concat!("/api/v1/", $url)), limit, $max);
+                let request = self.client.$method(&url)
+                    .headers(self.headers.clone());
+                let response = self.send(request)?;
 
                 Page::new(self, response)
             }
@@ -131,24 +148,19 @@ macro_rules! route {
 
             pub fn $name(&self, $($param: $typ,)*) -> Result<$ret> {
 
-                let form_data = json!({
-                    $(
-                        stringify!($param): $param,
-                    )*
-                });
+                // Leave out unset optional parameters rather than sending null.
+                let mut form_data = json::Map::new();
+                $(
+                    let value = json::to_value(&$param)?;
+                    if !value.is_null() {
+                        form_data.insert(stringify!($param).to_owned(), value);
+                    }
+                )*
 
-                let response = self.client.$method(&self.route(concat!("/api/v1/", $url)))
+                let request = self.client.$method(&self.route(concat!("/api/v1/", $url)))
                     .headers(self.headers.clone())
-                    .json(&form_data)
-                    .send()?;
-
-                let status = response.status().clone();
-
-                if status.is_client_error() {
-                    return Err(Error::Client(status));
-                } else if status.is_server_error() {
-                    return Err(Error::Server(status));
-                }
+                    .json(&form_data);
+                let response = self.send(request)?;
 
                 deserialise(response)
             }
@@ -176,22 +188,34 @@ macro_rules! route {
 
 macro_rules! route_id {
 
-    ($(($method:ident) $name:ident: $url:expr => $ret:ty,)*) => {
-        $(
-            doc_comment! {
-                concat!(
-                    "Equivalent to `/api/v1/",
-                    $url,
-                    "`\n# Errors\nIf `access_token` is not set."),
-                pub fn $name(&self, id: &str) -> Result<$ret> {
-                    self.$method(self.route(&format!(concat!("/api/v1/", $url), id)))
-                }
+    (($method:ident ($($param:ident: $typ:ty,)*)) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
+        doc_comment! {
+            concat!(
+                "Equivalent to `/api/v1/",
+                $url,
+                "`\n# Errors\nIf `access_token` is not set."),
+            pub fn $name(&self, id: &str, $($param: $typ,)*) -> Result<$ret> {
+                // Leave out unset optional parameters rather than sending null.
+                let mut form_data = json::Map::new();
+                $(
+                    let value = json::to_value(&$param)?;
+                    if !value.is_null() {
+                        form_data.insert(stringify!($param).to_owned(), value);
+                    }
+                )*
+
+                let url = self.route(&format!(concat!("/api/v1/", $url), id));
+                let request = self.client.$method(&url)
+                    .headers(self.headers.clone())
+                    .json(&form_data);
+                let response = self.send(request)?;
+
+                deserialise(response)
             }
-         )*
-    }
+        }
 
-}
-macro_rules! paged_routes_with_id {
+        route_id!{$($rest)*}
+    };
 
     (($method:ident) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
         doc_comment! {
@@ -199,11 +223,100 @@ macro_rules! paged_routes_with_id {
                 "Equivalent to `/api/v1/",
                 $url,
                 "`\n# Errors\nIf `access_token` is not set."),
-            pub fn $name(&self, id: &str) -> Result<Page<$ret>> {
-                let url = self.route(&format!(concat!("/api/v1/", $url), id));
-                let response = self.client.$method(&url)
+            pub fn $name(&self, id: &str) -> Result<$ret> {
+                self.$method(self.route(&format!(concat!("/api/v1/", $url), id)))
+            }
+        }
+
+        route_id!{$($rest)*}
+    };
+
+    () => {}
+}
+
+macro_rules! route_v2 {
+
+    (($method:ident) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
+        doc_comment! {
+            concat!(
+                "Equivalent to `/api/v2/",
+                $url,
+                "`\n# Errors\nIf `access_token` is not set."),
+            pub fn $name(&self) -> Result<$ret> {
+                self.$method(self.route(concat!("/api/v2/", $url)))
+            }
+        }
+
+        route_v2!{$($rest)*}
+    };
+
+    () => {}
+}
+
+macro_rules! route_v2_id {
+
+    (($method:ident ($($param:ident: $typ:ty,)*)) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
+        doc_comment! {
+            concat!(
+                "Equivalent to `/api/v2/",
+                $url,
+                "`\n# Errors\nIf `access_token` is not set."),
+            pub fn $name(&self, id: &str, $($param: $typ,)*) -> Result<$ret> {
+                // Leave out unset optional parameters rather than sending null.
+                let mut form_data = json::Map::new();
+                $(
+                    let value = json::to_value(&$param)?;
+                    if !value.is_null() {
+                        form_data.insert(stringify!($param).to_owned(), value);
+                    }
+                )*
+
+                let url = self.route(&format!(concat!("/api/v2/", $url), id));
+                let request = self.client.$method(&url)
                     .headers(self.headers.clone())
-                    .send()?;
+                    .json(&form_data);
+                let response = self.send(request)?;
+
+                deserialise(response)
+            }
+        }
+
+        route_v2_id!{$($rest)*}
+    };
+
+    (($method:ident) $name:ident: $url:expr => $ret:ty, $($rest:tt)*) => {
+        doc_comment! {
+            concat!(
+                "Equivalent to `/api/v2/",
+                $url,
+                "`\n# Errors\nIf `access_token` is not set."),
+            pub fn $name(&self, id: &str) -> Result<$ret> {
+                self.$method(self.route(&format!(concat!("/api/v2/", $url), id)))
+            }
+        }
+
+        route_v2_id!{$($rest)*}
+    };
+
+    () => {}
+}
+
+macro_rules! paged_routes_with_id {
+
+    (($method:ident) $name:ident: $url:expr => [$ret:ty; $max:ident], $($rest:tt)*) => {
+        doc_comment! {
+            concat!(
+                "Equivalent to `/api/v1/",
+                $url,
+                "`\n\nAt most `limit` items are returned per page, clamped to `",
+                stringify!($max),
+                "`.\n# Errors\nIf `access_token` is not set."),
+            pub fn $name(&self, id: &str, limit: Option<u32>) -> Result<Page<'_, $ret>> {
+                let url = self.route(&format!(concat!("/api/v1/", $url), id));
+                let url = with_limit(url, limit, $max);
+                let request = self.client.$method(&url)
+                    .headers(self.headers.clone());
+                let response = self.send(request)?;
 
                 Page::new(self, response)
             }
@@ -220,10 +333,73 @@ macro_rules! paged_routes_with_id {
 pub struct Mastodon {
     client: Client,
     headers: HeaderMap,
+    me: Arc<RwLock<Option<Account>>>,
+    rate_limit: Arc<RwLock<Option<RateLimit>>>,
+    default_visibility: Option<Visibility>,
+    default_language: Option<String>,
     /// Raw data about your mastodon instance.
     pub data: Data,
 }
 
+/// Builder for a `Mastodon` client with extra options, from
+/// `Mastodon::builder`.
+///
+/// ```
+/// # extern crate mammut;
+/// # fn main() -> mammut::Result<()> {
+/// use mammut::{Data, Mastodon};
+///
+/// let data = Data {
+///     base: "https://mastodon.social".into(),
+///     client_id: "id".into(),
+///     client_secret: "secret".into(),
+///     redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+///     token: "token".into(),
+///     token_kind: Default::default(),
+/// };
+///
+/// let client = Mastodon::builder(data.clone())
+///     .user_agent("MyBot/1.0 (+https://example.com/bot)")
+///     .build()?;
+///
+/// assert!(Mastodon::builder(data).user_agent("MyBot\n").build().is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MastodonBuilder {
+    data: Data,
+    user_agent: Option<String>,
+}
+
+impl MastodonBuilder {
+    /// Set the `User-Agent` header sent with every request, so the
+    /// instance's admins can tell which app is making them.
+    pub fn user_agent<I: Into<String>>(mut self, user_agent: I) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Create the `Mastodon` client.
+    ///
+    /// # Errors
+    /// If the user agent can't be sent as a header, such as when it contains
+    /// a newline, an `Error::InvalidHeaderValue` is returned.
+    pub fn build(self) -> Result<Mastodon> {
+        let user_agent = match self.user_agent {
+            Some(ref user_agent) => Some(HeaderValue::from_str(user_agent)?),
+            None => None,
+        };
+
+        let mut mastodon = Mastodon::from_data(self.data);
+        if let Some(user_agent) = user_agent {
+            mastodon.headers.insert(header::USER_AGENT, user_agent);
+        }
+
+        Ok(mastodon)
+    }
+}
+
 /// Raw data about mastodon app. Save `Data` using `serde` to prevent needing
 /// to authenticate on every run.
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
@@ -238,78 +414,644 @@ pub struct Data {
     pub redirect: Cow<'static, str>,
     /// The client's access token.
     pub token: Cow<'static, str>,
+    /// Whether `token` acts for a user or only for the app. Missing from
+    /// `Data` saved by older versions, which only had user tokens.
+    #[serde(default)]
+    pub token_kind: TokenKind,
+}
+
+/// Who an access token acts for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TokenKind {
+    /// A user authorised the app, with `Registered::complete`.
+    #[default]
+    #[serde(rename = "user")]
+    User,
+    /// The app by itself, without a user, from
+    /// `Registered::create_app_token`. It can only use routes that don't
+    /// need a user, such as reading public timelines.
+    #[serde(rename = "app")]
+    App,
+}
+
+/// Whether an access token is still valid, from `Mastodon::check_auth`.
+#[derive(Clone, Debug)]
+pub enum AuthStatus {
+    /// A user's token is valid, and acts for this account.
+    User(Box<Account>),
+    /// An app token is valid, and acts for this app.
+    App(Application),
+    /// The token is missing, or the instance refused it. The user needs to
+    /// log in again.
+    Invalid,
+}
+
+impl Data {
+    /// Load `Data` previously saved as JSON with `save_to_file`. These are
+    /// also available as `helpers::json::from_file` and `to_file`, alongside
+    /// the other JSON helpers.
+    ///
+    /// # Errors
+    /// If the file doesn't exist or can't be read, an `Error::Io` is returned,
+    /// and if it doesn't contain valid `Data`, such as a file saved by an
+    /// older version, an `Error::InvalidData`.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use mammut::Data;
+    ///
+    /// let data = Data {
+    ///     base: "https://mastodon.social".into(),
+    ///     client_id: "id".into(),
+    ///     client_secret: "secret".into(),
+    ///     redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+    ///     token: "token".into(),
+    ///     token_kind: Default::default(),
+    /// };
+    ///
+    /// let path = std::env::temp_dir().join("mammut-data.json");
+    /// data.save_to_file(&path)?;
+    /// assert_eq!(Data::from_file(&path)?, data);
+    /// # std::fs::remove_file(path)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        json::from_reader(BufReader::new(file)).map_err(helpers::invalid_json)
+    }
+
+    /// Save `Data` as JSON to the file at `path`, overwriting it if it already
+    /// exists and creating its parent directories. On unix the file can only
+    /// be read by its owner, as it contains the access token.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(helpers::create_private(path.as_ref())?);
+        json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Load `Data` from the environment variables `MASTODON_BASE`,
+    /// `MASTODON_CLIENT_ID`, `MASTODON_CLIENT_SECRET`,
+    /// `MASTODON_ACCESS_TOKEN` and `MASTODON_REDIRECT`, such as for a bot
+    /// running in a container. See `from_env_with_prefix` to use another
+    /// prefix than `MASTODON`.
+    ///
+    /// `MASTODON_REDIRECT` can be left unset, defaulting to
+    /// `urn:ietf:wg:oauth:2.0:oob`. The token is taken to be a user's.
+    /// Whitespace around the values, such as the newline left by
+    /// `$(cat token)`, is removed.
+    ///
+    /// # Errors
+    /// If a variable isn't set, or isn't valid unicode, an `Error::EnvVar`
+    /// naming it is returned. If the token can't be sent in a header, such
+    /// as when it contains a control character, an
+    /// `Error::InvalidHeaderValue` is returned.
+    pub fn from_env() -> Result<Self> {
+        Self::from_env_with_prefix("MASTODON")
+    }
+
+    /// Load `Data` from environment variables like `from_env`, starting with
+    /// `prefix` followed by an underscore instead of `MASTODON_`.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # fn main() -> mammut::Result<()> {
+    /// use mammut::{Data, Error};
+    ///
+    /// std::env::set_var("MYBOT_BASE", "https://mastodon.social");
+    /// std::env::set_var("MYBOT_CLIENT_ID", "id");
+    /// std::env::set_var("MYBOT_CLIENT_SECRET", "secret");
+    ///
+    /// match Data::from_env_with_prefix("MYBOT") {
+    ///     Err(Error::EnvVar(ref name, _)) => assert_eq!(name, "MYBOT_ACCESS_TOKEN"),
+    ///     _ => panic!("the token is missing"),
+    /// }
+    ///
+    /// std::env::set_var("MYBOT_ACCESS_TOKEN", "it's\ra secret");
+    /// match Data::from_env_with_prefix("MYBOT") {
+    ///     Err(Error::InvalidHeaderValue(_)) => {}
+    ///     _ => panic!("the token can't be sent in a header"),
+    /// }
+    ///
+    /// std::env::set_var("MYBOT_ACCESS_TOKEN", "it's a secret\n");
+    /// let data = Data::from_env_with_prefix("MYBOT")?;
+    /// assert_eq!(data.token, "it's a secret");
+    /// assert_eq!(data.redirect, "urn:ietf:wg:oauth:2.0:oob");
+    /// assert!(data
+    ///     .to_env_exports_with_prefix("MYBOT")
+    ///     .contains(r#"export MYBOT_ACCESS_TOKEN='it'\''s a secret'"#));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_env_with_prefix(prefix: &str) -> Result<Self> {
+        let var = |name: &str| {
+            let name = format!("{}_{}", prefix, name);
+            env::var(&name)
+                .map(|value| value.trim().to_owned())
+                .map_err(|e| Error::EnvVar(name, e))
+        };
+
+        let redirect = match var("REDIRECT") {
+            Err(Error::EnvVar(_, VarError::NotPresent)) => {
+                String::from("urn:ietf:wg:oauth:2.0:oob")
+            }
+            redirect => redirect?,
+        };
+        let token = var("ACCESS_TOKEN")?;
+        auth_header(&token)?;
+
+        Ok(Data {
+            base: var("BASE")?.into(),
+            client_id: var("CLIENT_ID")?.into(),
+            client_secret: var("CLIENT_SECRET")?.into(),
+            redirect: redirect.into(),
+            token: token.into(),
+            token_kind: TokenKind::User,
+        })
+    }
+
+    /// Shell `export` lines setting the variables read by `from_env`, one
+    /// per line.
+    pub fn to_env_exports(&self) -> String {
+        self.to_env_exports_with_prefix("MASTODON")
+    }
+
+    /// Shell `export` lines setting the variables read by
+    /// `from_env_with_prefix` with the same `prefix`.
+    pub fn to_env_exports_with_prefix(&self, prefix: &str) -> String {
+        let vars = [
+            ("BASE", &self.base),
+            ("CLIENT_ID", &self.client_id),
+            ("CLIENT_SECRET", &self.client_secret),
+            ("REDIRECT", &self.redirect),
+            ("ACCESS_TOKEN", &self.token),
+        ];
+
+        vars.iter()
+            .map(|(name, value)| {
+                // Single quoted, with each `'` closing the quotes, adding an
+                // escaped `'` and opening them again.
+                let value = value.replace('\'', r"'\''");
+                format!("export {}_{}='{}'\n", prefix, name, value)
+            })
+            .collect()
+    }
 }
 
 /// enum of possible errors encountered using the mastodon API.
-#[derive(Debug, Deserialize)]
-#[serde(untagged)]
+///
+/// Errors wrapping another error return it from `source`.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate url;
+/// use mammut::Error;
+/// use std::error::Error as StdError;
+///
+/// let error = Error::from(url::Url::parse("not a url").unwrap_err());
+/// assert!(error.source().is_some());
+/// assert!(Error::AccessTokenRequired.source().is_none());
+/// assert_eq!(
+///     Error::AccessTokenRequired.to_string(),
+///     "access token required; authenticate first"
+/// );
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Error from the Mastodon API. This typically means something went
     /// wrong with your authentication or data.
     Api(ApiError),
     /// Error deserialising to json. Typically represents a breaking change in
     /// the Mastodon API
-    #[serde(skip_deserializing)]
     Serde(SerdeError),
+    /// A successful response from the instance that didn't match the entity
+    /// it should contain, such as when a newer server changes a field's
+    /// type.
+    Deserialize {
+        /// Why the response didn't match.
+        source: SerdeError,
+        /// The start of the response's body, to show what didn't match.
+        body_snippet: String,
+    },
     /// Error encountered in the HTTP backend while requesting a route.
-    #[serde(skip_deserializing)]
     Http(HttpError),
     /// Wrapper around the `std::io::Error` struct.
-    #[serde(skip_deserializing)]
     Io(IoError),
     /// Wrapper around the `url::ParseError` struct.
-    #[serde(skip_deserializing)]
     Url(UrlError),
     /// Missing Client Id.
-    #[serde(skip_deserializing)]
     ClientIdRequired,
     /// Missing Client Secret.
-    #[serde(skip_deserializing)]
     ClientSecretRequired,
+    /// Missing application name.
+    ClientNameRequired,
     /// Missing Access Token.
-    #[serde(skip_deserializing)]
     AccessTokenRequired,
     /// Generic client error.
-    #[serde(skip_deserializing)]
     Client(StatusCode),
     /// Generic server error.
-    #[serde(skip_deserializing)]
     Server(StatusCode),
+    /// The instance's rate limit was exceeded, with a `429 Too Many Requests`
+    /// response. Requests can be made again after `reset`.
+    ///
+    /// This needs all of the `X-RateLimit-*` headers to parse, so a `429`
+    /// response without them is returned as `Error::Client` instead. Use
+    /// `Error::is_rate_limited` to check for both.
+    RateLimited {
+        /// When the rate limit resets.
+        reset: DateTime<Utc>,
+    },
     /// A possible error when converting a HeaderValue to a string representation.
-    #[serde(skip_deserializing)]
     Header(HeaderToStrError),
+    /// A value that can't be sent in a header, such as a user agent with a
+    /// newline.
+    InvalidHeaderValue(InvalidHeaderValue),
     /// Errors while parsing headers and associated types.
-    #[serde(skip_deserializing)]
     Hyperx(HyperxError),
+    /// An attachment was still being processed when `wait_for_media` timed
+    /// out.
+    ProcessingTimeout,
+    /// A request that wasn't sent, such as for the statuses `reblog_many`
+    /// and `favourite_many` skip after the rate limit was reached.
+    NotSent,
+    /// A media focal point with a coordinate outside of `-1.0` to `1.0`.
+    InvalidFocus(f32, f32),
+    /// A response whose body wasn't JSON, such as an HTML `502 Bad Gateway`
+    /// page from a proxy in front of the instance.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// use std::io::{BufRead, BufReader, Write};
+    /// use std::net::TcpListener;
+    /// use std::thread;
+    ///
+    /// use mammut::{Data, Error, Mastodon};
+    ///
+    /// // Answer each request with the next of these responses.
+    /// let responses = [
+    ///     "HTTP/1.1 502 Bad Gateway\r\nContent-Type: text/html\r\n\
+    ///      Content-Length: 26\r\nConnection: close\r\n\r\n<h1>502 Bad Gateway</h1>\r\n",
+    ///     "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    ///     "HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n",
+    /// ];
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let base = format!("http://{}", listener.local_addr().unwrap());
+    /// let server = thread::spawn(move || {
+    ///     for response in responses.iter() {
+    ///         let (mut stream, _) = listener.accept().unwrap();
+    ///         let mut reader = BufReader::new(stream.try_clone().unwrap());
+    ///         let mut line = String::new();
+    ///         while reader.read_line(&mut line).unwrap() > 2 {
+    ///             line.clear();
+    ///         }
+    ///         stream.write_all(response.as_bytes()).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let client = Mastodon::from_data(Data {
+    ///     base: base.into(),
+    ///     client_id: "id".into(),
+    ///     client_secret: "secret".into(),
+    ///     redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+    ///     token: "token".into(),
+    ///     token_kind: Default::default(),
+    /// });
+    ///
+    /// match client.delete_status("1") {
+    ///     Err(Error::UnexpectedResponse {
+    ///         status,
+    ///         content_type,
+    ///         body,
+    ///     }) => {
+    ///         assert_eq!(status.as_u16(), 502);
+    ///         assert_eq!(content_type.as_ref().map(String::as_str), Some("text/html"));
+    ///         assert!(body.contains("Bad Gateway"));
+    ///     }
+    ///     other => panic!("expected the error page, got {:?}", other.map(|_| ())),
+    /// }
+    ///
+    /// // Empty successful responses are fine for routes without a result.
+    /// assert!(client.delete_status("1").is_ok());
+    /// assert!(client.delete_status("1").is_ok());
+    /// server.join().unwrap();
+    /// ```
+    UnexpectedResponse {
+        /// The status of the response.
+        status: StatusCode,
+        /// The response's `Content-Type`, if it has one.
+        content_type: Option<String>,
+        /// The raw body of the response.
+        body: String,
+    },
+    /// A redirect uri that isn't one of those the app was registered with.
+    RedirectMismatch(String),
+    /// A handle given to `registration::discover_instance` that isn't of the
+    /// form `@user@domain`.
+    InvalidHandle(String),
+    /// A handle given to `Mastodon::follow_by_acct` that the instance found
+    /// no account for, although the search itself succeeded.
+    AccountNotFound(String),
+    /// Saved `Data` that couldn't be read, such as from an older version
+    /// missing a field. Contains the reason.
+    InvalidData(String),
+    /// `Data` that couldn't be written in the format it was being saved in.
+    /// Contains the reason.
+    SaveData(String),
+    /// An environment variable read by `Data::from_env` that isn't set or
+    /// isn't valid unicode. Contains the name of the variable.
+    EnvVar(String, VarError),
+}
+
+impl Error {
+    /// The status of the response that caused the error, if it was caused by
+    /// one. This tells apart errors such as a route that needs
+    /// authentication, with a `401`, and one that is turned off, with a
+    /// `404`.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # extern crate reqwest;
+    /// use mammut::Error;
+    /// use reqwest::StatusCode;
+    ///
+    /// let error = Error::Client(StatusCode::NOT_FOUND);
+    /// assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+    /// assert_eq!(Error::AccessTokenRequired.status(), None);
+    /// ```
+    pub fn status(&self) -> Option<StatusCode> {
+        match *self {
+            Error::Api(ref e) => e.status,
+            Error::Client(status)
+            | Error::Server(status)
+            | Error::UnexpectedResponse { status, .. } => Some(status),
+            Error::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            Error::Http(ref e) => e.status(),
+            Error::Serde(_)
+            | Error::Deserialize { .. }
+            | Error::Io(_)
+            | Error::Url(_)
+            | Error::Header(_)
+            | Error::InvalidHeaderValue(_)
+            | Error::Hyperx(_)
+            | Error::InvalidFocus(..)
+            | Error::ProcessingTimeout
+            | Error::NotSent
+            | Error::RedirectMismatch(_)
+            | Error::InvalidHandle(_)
+            | Error::AccountNotFound(_)
+            | Error::InvalidData(_)
+            | Error::SaveData(_)
+            | Error::EnvVar(..)
+            | Error::ClientIdRequired
+            | Error::ClientSecretRequired
+            | Error::ClientNameRequired
+            | Error::AccessTokenRequired => None,
+        }
+    }
+
+    /// Whether the instance couldn't be reached, because looking up its
+    /// domain or connecting to it failed. These are usually worth retrying
+    /// later. A failed TLS handshake isn't counted, as it's reported by the
+    /// TLS library rather than as an I/O error.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// use std::net::TcpListener;
+    ///
+    /// use mammut::{Data, Mastodon};
+    ///
+    /// // A port that was just free, so connecting to it is refused.
+    /// let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    ///
+    /// let data = Data {
+    ///     base: format!("http://{}", addr).into(),
+    ///     client_id: "".into(),
+    ///     client_secret: "".into(),
+    ///     redirect: "".into(),
+    ///     token: "".into(),
+    ///     token_kind: Default::default(),
+    /// };
+    ///
+    /// let error = Mastodon::from_data(data).instance().unwrap_err();
+    /// assert!(error.is_connect_error());
+    /// assert!(!error.is_server_error());
+    /// ```
+    pub fn is_connect_error(&self) -> bool {
+        match *self {
+            Error::Http(ref e) => {
+                let mut cause = e.get_ref().map(|e| e as &(dyn StdError + 'static));
+
+                while let Some(error) = cause {
+                    if error.is::<IoError>() {
+                        return !e.is_timeout();
+                    }
+                    cause = error.source();
+                }
+
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the request timed out, while connecting or waiting for the
+    /// response.
+    pub fn is_timeout(&self) -> bool {
+        match *self {
+            Error::Http(ref e) => e.is_timeout(),
+            Error::Io(ref e) => e.kind() == std::io::ErrorKind::TimedOut,
+            _ => false,
+        }
+    }
+
+    /// Whether the instance responded with a `5xx` status, meaning the
+    /// request failed because of a problem with the instance.
+    pub fn is_server_error(&self) -> bool {
+        self.status()
+            .map_or(false, |status| status.is_server_error())
+    }
+
+    /// Whether the instance responded with `401 Unauthorized`, such as when
+    /// the access token was revoked and the user needs to authenticate again.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # extern crate reqwest;
+    /// use mammut::{ApiError, Error};
+    /// use reqwest::StatusCode;
+    ///
+    /// let error = Error::Api(ApiError {
+    ///     error: Some("The access token was revoked".into()),
+    ///     error_description: None,
+    ///     status: Some(StatusCode::UNAUTHORIZED),
+    /// });
+    /// assert!(error.is_unauthorized());
+    /// assert!(Error::Client(StatusCode::UNAUTHORIZED).is_unauthorized());
+    /// assert!(!error.is_not_found());
+    /// ```
+    pub fn is_unauthorized(&self) -> bool {
+        self.status() == Some(StatusCode::UNAUTHORIZED)
+    }
+
+    /// Whether the instance responded with `404 Not Found`, such as for a
+    /// deleted status or a wrong instance url.
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// Whether the instance responded with `422 Unprocessable Entity`,
+    /// meaning it rejected the request's parameters, such as a status that
+    /// is too long.
+    pub fn is_unprocessable(&self) -> bool {
+        self.status() == Some(StatusCode::UNPROCESSABLE_ENTITY)
+    }
+
+    /// Whether the instance responded with `429 Too Many Requests`, meaning
+    /// the request should be retried later.
+    pub fn is_rate_limited(&self) -> bool {
+        self.status() == Some(StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// Whether the instance refused the credentials or code used to get an
+    /// access token, such as a wrong password given to
+    /// `Registered::login_with_password`.
+    ///
+    /// ```
+    /// use mammut::{ApiError, Error};
+    ///
+    /// let error = Error::Api(ApiError {
+    ///     error: Some("invalid_grant".into()),
+    ///     error_description: None,
+    ///     status: None,
+    /// });
+    /// assert!(error.is_invalid_grant());
+    /// assert!(!error.is_connect_error());
+    /// ```
+    pub fn is_invalid_grant(&self) -> bool {
+        match *self {
+            Error::Api(ref e) => e.error.as_deref() == Some("invalid_grant"),
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        match *self {
+            Error::Api(ref e) => match (e.error_description.as_ref(), e.error.as_ref()) {
+                (Some(message), _) | (None, Some(message)) => {
+                    write!(f, "the server returned an error: {}", message)
+                }
+                (None, None) => write!(f, "the server returned an unknown error"),
+            },
+            Error::Serde(ref e) => write!(f, "couldn't parse the server's response: {}", e),
+            Error::Deserialize {
+                ref source,
+                ref body_snippet,
+            } => write!(
+                f,
+                "couldn't parse the server's response: {}; the response began: {}",
+                source, body_snippet
+            ),
+            Error::Http(ref e) => e.fmt(f),
+            Error::Io(ref e) => e.fmt(f),
+            Error::Url(ref e) => write!(f, "invalid URL: {}", e),
+            Error::ClientIdRequired => write!(f, "client id required; register the app first"),
+            Error::ClientSecretRequired => {
+                write!(f, "client secret required; register the app first")
+            }
+            Error::ClientNameRequired => write!(f, "the app needs a client name"),
+            Error::AccessTokenRequired => write!(f, "access token required; authenticate first"),
+            Error::Client(status) => write!(f, "the request was rejected: {}", status),
+            Error::Server(status) => write!(f, "the server failed to respond: {}", status),
+            Error::RateLimited { reset } => {
+                write!(f, "the rate limit was exceeded; it resets at {}", reset)
+            }
+            Error::Header(ref e) => write!(f, "invalid header value: {}", e),
+            Error::InvalidHeaderValue(ref e) => write!(f, "invalid header value: {}", e),
+            Error::Hyperx(ref e) => write!(f, "couldn't parse a header: {}", e),
+            Error::ProcessingTimeout => {
+                write!(f, "the server was still processing the attachment")
+            }
+            Error::NotSent => write!(
+                f,
+                "the request wasn't sent because an earlier one was rate limited"
+            ),
+            Error::InvalidFocus(x, y) => write!(
+                f,
+                "focus ({}, {}) is out of range; both coordinates must be from -1 to 1",
+                x, y
+            ),
+            Error::UnexpectedResponse {
+                status,
+                ref content_type,
+                ..
+            } => {
+                write!(f, "the server sent a response that isn't JSON: {}", status)?;
+                match *content_type {
+                    Some(ref content_type) => write!(f, " ({})", content_type),
+                    None => Ok(()),
+                }
+            }
+            Error::EnvVar(ref name, VarError::NotPresent) => {
+                write!(f, "the environment variable {} isn't set", name)
+            }
+            Error::EnvVar(ref name, VarError::NotUnicode(_)) => {
+                write!(f, "the environment variable {} isn't valid unicode", name)
+            }
+            Error::InvalidData(ref reason) => write!(
+                f,
+                "the saved data is invalid, it may be from an older version; authenticate \
+                 again to replace it: {}",
+                reason
+            ),
+            Error::SaveData(ref reason) => write!(f, "couldn't save the data: {}", reason),
+            Error::RedirectMismatch(ref uri) => write!(
+                f,
+                "the app wasn't registered with the redirect uri {}; redirect uris must match \
+                 exactly, including any trailing slash",
+                uri
+            ),
+            Error::InvalidHandle(ref handle) => {
+                write!(f, "{} isn't a handle of the form @user@domain", handle)
+            }
+            Error::AccountNotFound(ref acct) => write!(f, "no account found for {}", acct),
+        }
     }
 }
 
 impl StdError for Error {
-    fn description(&self) -> &str {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self {
-            Error::Api(ref e) => e
-                .error_description
-                .as_ref()
-                .map(|i| &**i)
-                .or(e.error.as_ref().map(|i| &**i))
-                .unwrap_or("Unknown API Error"),
-            Error::Serde(ref e) => e.description(),
-            Error::Http(ref e) => e.description(),
-            Error::Io(ref e) => e.description(),
-            Error::Url(ref e) => e.description(),
-            Error::Client(ref status) | Error::Server(ref status) => {
-                status.canonical_reason().unwrap_or("Unknown Status code")
-            }
-            Error::Hyperx(ref e) => e.description(),
-            Error::Header(ref e) => e.description(),
-            Error::ClientIdRequired => "ClientIdRequired",
-            Error::ClientSecretRequired => "ClientSecretRequired",
-            Error::AccessTokenRequired => "AccessTokenRequired",
+            Error::Serde(ref e) => Some(e),
+            Error::Deserialize { ref source, .. } => Some(source),
+            Error::Http(ref e) => Some(e),
+            Error::Io(ref e) => Some(e),
+            Error::Url(ref e) => Some(e),
+            Error::Hyperx(ref e) => Some(e),
+            Error::Header(ref e) => Some(e),
+            Error::InvalidHeaderValue(ref e) => Some(e),
+            Error::EnvVar(_, ref e) => Some(e),
+            Error::Api(_)
+            | Error::Client(_)
+            | Error::Server(_)
+            | Error::RateLimited { .. }
+            | Error::UnexpectedResponse { .. }
+            | Error::InvalidFocus(..)
+            | Error::ProcessingTimeout
+            | Error::NotSent
+            | Error::RedirectMismatch(_)
+            | Error::InvalidHandle(_)
+            | Error::AccountNotFound(_)
+            | Error::InvalidData(_)
+            | Error::SaveData(_)
+            | Error::ClientIdRequired
+            | Error::ClientSecretRequired
+            | Error::ClientNameRequired
+            | Error::AccessTokenRequired => None,
         }
     }
 }
@@ -320,6 +1062,12 @@ impl From<HyperxError> for Error {
     }
 }
 
+impl From<InvalidHeaderValue> for Error {
+    fn from(error: InvalidHeaderValue) -> Self {
+        Error::InvalidHeaderValue(error)
+    }
+}
+
 impl From<HeaderToStrError> for Error {
     fn from(error: HeaderToStrError) -> Self {
         Error::Header(error)
@@ -333,6 +1081,9 @@ pub struct ApiError {
     pub error: Option<String>,
     /// The description of the error.
     pub error_description: Option<String>,
+    /// The status of the response the error was in.
+    #[serde(skip)]
+    pub status: Option<StatusCode>,
 }
 
 /// # Example
@@ -345,6 +1096,7 @@ pub struct ApiError {
 ///                               .pinned()
 ///                               .since_id("foo");
 /// # assert_eq!(&request.to_querystring()[..], "?only_media=1&pinned=1&since_id=foo");
+/// # assert_eq!(&StatusesRequest::new().limit(1000).to_querystring()[..], "?limit=40");
 /// ```
 #[derive(Clone, Debug, Default)]
 pub struct StatusesRequest<'a> {
@@ -359,103 +1111,667 @@ pub struct StatusesRequest<'a> {
 }
 
 impl<'a> StatusesRequest<'a> {
+    /// Construct a new `StatusesRequest` object
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Set the `?only_media=1` flag for the .statuses() request
     pub fn only_media(mut self) -> Self {
         self.only_media = true;
         self
     }
 
+    /// Set the `?exclude_replies=1` flag for the .statuses() request
     pub fn exclude_replies(mut self) -> Self {
         self.exclude_replies = true;
         self
     }
 
+    /// Set the `?pinned=1` flag for the .statuses() request
     pub fn pinned(mut self) -> Self {
         self.pinned = true;
         self
     }
 
+    /// Set the `?max_id=:max_id` flag for the .statuses() request
     pub fn max_id<S: Into<Cow<'a, str>>>(mut self, max_id: S) -> Self {
         self.max_id = Some(max_id.into());
         self
     }
 
+    /// Set the `?since_id=:since_id` flag for the .statuses() request. This
+    /// returns the newest statuses after `since_id`, so if there are more
+    /// than fit in a page there is a gap between them and `since_id`. Use
+    /// `min_id` to catch up without gaps.
     pub fn since_id<S: Into<Cow<'a, str>>>(mut self, since_id: S) -> Self {
         self.since_id = Some(since_id.into());
         self
     }
 
+    /// Set the `?min_id=:min_id` flag for the .statuses() request. This
+    /// returns the oldest statuses after `min_id`, immediately following it,
+    /// so paging forwards from it doesn't skip any statuses.
     pub fn min_id<S: Into<Cow<'a, str>>>(mut self, min_id: S) -> Self {
         self.min_id = Some(min_id.into());
         self
     }
 
+    /// Set the `?limit=:limit` flag for the .statuses() request, clamped to
+    /// `MAX_STATUSES_LIMIT`.
     pub fn limit(mut self, limit: usize) -> Self {
-        self.limit = Some(limit);
+        self.limit = Some(limit.min(MAX_STATUSES_LIMIT as usize));
         self
     }
 
+    /// Set the `?exclude_reblogs=1` flag for the .statuses() request
     pub fn exclude_reblogs(mut self) -> Self {
         self.exclude_reblogs = true;
         self
     }
 
-    pub fn to_querystring(&self) -> String {
-        let mut opts = vec![];
+    fn pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
 
         if self.only_media {
-            opts.push("only_media=1".into());
+            pairs.push(("only_media", "1".into()));
         }
 
         if self.exclude_replies {
-            opts.push("exclude_replies=1".into());
+            pairs.push(("exclude_replies", "1".into()));
         }
 
         if self.pinned {
-            opts.push("pinned=1".into());
+            pairs.push(("pinned", "1".into()));
         }
 
         if let Some(ref max_id) = self.max_id {
-            opts.push(format!("max_id={}", max_id));
+            pairs.push(("max_id", max_id.to_string()));
         }
 
         if let Some(ref since_id) = self.since_id {
-            opts.push(format!("since_id={}", since_id));
+            pairs.push(("since_id", since_id.to_string()));
         }
 
         if let Some(ref min_id) = self.min_id {
-            opts.push(format!("min_id={}", min_id));
+            pairs.push(("min_id", min_id.to_string()));
         }
 
         if let Some(limit) = self.limit {
-            opts.push(format!("limit={}", limit));
+            pairs.push(("limit", limit.to_string()));
         }
 
         if self.exclude_reblogs {
-            opts.push("exclude_reblogs=1".into());
+            pairs.push(("exclude_reblogs", "1".into()));
         }
 
-        if opts.is_empty() {
+        pairs
+    }
+
+    /// Serialize into a query string
+    pub fn to_querystring(&self) -> String {
+        let pairs = self.pairs();
+
+        if pairs.is_empty() {
             String::new()
         } else {
-            format!("?{}", opts.join("&"))
+            let query = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(pairs)
+                .finish();
+            format!("?{}", query)
         }
     }
 }
 
-impl Mastodon {
-    fn from_registration<I>(
-        base: I,
-        client_id: I,
-        client_secret: I,
-        redirect: I,
-        token: I,
-        client: Client,
-    ) -> Self
-    where
+/// Options for fetching the public timeline with
+/// `Mastodon::get_public_timeline_with`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate mammut;
+/// # use mammut::PublicTimelineRequest;
+/// let request = PublicTimelineRequest::new()
+///     .remote()
+///     .only_media()
+///     .max_id("103");
+/// # assert_eq!(&request.to_querystring()[..], "?remote=true&only_media=true&max_id=103");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PublicTimelineRequest<'a> {
+    local: bool,
+    remote: bool,
+    only_media: bool,
+    max_id: Option<Cow<'a, str>>,
+    since_id: Option<Cow<'a, str>>,
+    min_id: Option<Cow<'a, str>>,
+    limit: Option<usize>,
+}
+
+impl<'a> PublicTimelineRequest<'a> {
+    /// Construct a new `PublicTimelineRequest` object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only return statuses posted on the instance.
+    pub fn local(mut self) -> Self {
+        self.local = true;
+        self
+    }
+
+    /// Only return statuses posted on other instances.
+    pub fn remote(mut self) -> Self {
+        self.remote = true;
+        self
+    }
+
+    /// Only return statuses with media attachments.
+    pub fn only_media(mut self) -> Self {
+        self.only_media = true;
+        self
+    }
+
+    /// Only return statuses older than this status.
+    pub fn max_id<S: Into<Cow<'a, str>>>(mut self, max_id: S) -> Self {
+        self.max_id = Some(max_id.into());
+        self
+    }
+
+    /// Only return statuses newer than this status, the newest first.
+    pub fn since_id<S: Into<Cow<'a, str>>>(mut self, since_id: S) -> Self {
+        self.since_id = Some(since_id.into());
+        self
+    }
+
+    /// Only return statuses newer than this status, the oldest of them
+    /// first.
+    pub fn min_id<S: Into<Cow<'a, str>>>(mut self, min_id: S) -> Self {
+        self.min_id = Some(min_id.into());
+        self
+    }
+
+    /// Set the maximum number of statuses to return, clamped to
+    /// `MAX_STATUSES_LIMIT`.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit.min(MAX_STATUSES_LIMIT as usize));
+        self
+    }
+
+    fn pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
+
+        if self.local {
+            pairs.push(("local", "true".into()));
+        }
+
+        if self.remote {
+            pairs.push(("remote", "true".into()));
+        }
+
+        if self.only_media {
+            pairs.push(("only_media", "true".into()));
+        }
+
+        if let Some(ref max_id) = self.max_id {
+            pairs.push(("max_id", max_id.to_string()));
+        }
+
+        if let Some(ref since_id) = self.since_id {
+            pairs.push(("since_id", since_id.to_string()));
+        }
+
+        if let Some(ref min_id) = self.min_id {
+            pairs.push(("min_id", min_id.to_string()));
+        }
+
+        if let Some(limit) = self.limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+
+        pairs
+    }
+
+    /// Serialize into a query string
+    pub fn to_querystring(&self) -> String {
+        let pairs = self.pairs();
+
+        if pairs.is_empty() {
+            String::new()
+        } else {
+            let query = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(pairs)
+                .finish();
+            format!("?{}", query)
+        }
+    }
+}
+
+/// Options for searching with `Mastodon::search_v2`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate mammut;
+/// # use mammut::{SearchRequest, SearchType};
+/// let request = SearchRequest::new()
+///     .search_type(SearchType::Hashtags)
+///     .limit(10)
+///     .offset(20);
+/// # assert_eq!(&request.to_querystring()[..], "?type=hashtags&limit=10&offset=20");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SearchRequest<'a> {
+    search_type: Option<SearchType>,
+    resolve: bool,
+    following: bool,
+    account_id: Option<Cow<'a, str>>,
+    exclude_unreviewed: bool,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+/// The kind of result to search for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchType {
+    /// Only search for accounts.
+    Accounts,
+    /// Only search for hashtags.
+    Hashtags,
+    /// Only search for statuses.
+    Statuses,
+}
+
+impl<'a> SearchRequest<'a> {
+    /// Construct a new `SearchRequest` object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only return results of this type.
+    pub fn search_type(mut self, search_type: SearchType) -> Self {
+        self.search_type = Some(search_type);
+        self
+    }
+
+    /// Look up remote accounts and statuses by their URL or handle.
+    pub fn resolve(mut self) -> Self {
+        self.resolve = true;
+        self
+    }
+
+    /// Only return accounts the user follows.
+    pub fn following(mut self) -> Self {
+        self.following = true;
+        self
+    }
+
+    /// Only return statuses posted by this account.
+    pub fn account_id<S: Into<Cow<'a, str>>>(mut self, account_id: S) -> Self {
+        self.account_id = Some(account_id.into());
+        self
+    }
+
+    /// Leave out hashtags that haven't been reviewed by moderators.
+    pub fn exclude_unreviewed(mut self) -> Self {
+        self.exclude_unreviewed = true;
+        self
+    }
+
+    /// Set the maximum number of results of each type, clamped to
+    /// `MAX_STATUSES_LIMIT`.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit.min(MAX_STATUSES_LIMIT as usize));
+        self
+    }
+
+    /// Skip this many results, to load the results after those already
+    /// fetched. Only used when a `search_type` is set.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    fn pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
+
+        match self.search_type {
+            Some(SearchType::Accounts) => pairs.push(("type", "accounts".into())),
+            Some(SearchType::Hashtags) => pairs.push(("type", "hashtags".into())),
+            Some(SearchType::Statuses) => pairs.push(("type", "statuses".into())),
+            None => {}
+        }
+
+        if self.resolve {
+            pairs.push(("resolve", "true".into()));
+        }
+
+        if self.following {
+            pairs.push(("following", "true".into()));
+        }
+
+        if let Some(ref account_id) = self.account_id {
+            pairs.push(("account_id", account_id.to_string()));
+        }
+
+        if self.exclude_unreviewed {
+            pairs.push(("exclude_unreviewed", "true".into()));
+        }
+
+        if let Some(limit) = self.limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+
+        if let Some(offset) = self.offset {
+            pairs.push(("offset", offset.to_string()));
+        }
+
+        pairs
+    }
+
+    /// Serialize into a query string
+    pub fn to_querystring(&self) -> String {
+        let pairs = self.pairs();
+
+        if pairs.is_empty() {
+            String::new()
+        } else {
+            let query = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(pairs)
+                .finish();
+            format!("?{}", query)
+        }
+    }
+}
+
+/// The timelines to fetch markers for with `Mastodon::get_markers`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate mammut;
+/// # use mammut::MarkersRequest;
+/// let request = MarkersRequest::new().home().notifications();
+/// assert_eq!(
+///     &request.to_querystring()[..],
+///     "?timeline[]=home&timeline[]=notifications"
+/// );
+/// assert_eq!(&MarkersRequest::new().to_querystring()[..], "");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MarkersRequest {
+    home: bool,
+    notifications: bool,
+}
+
+impl MarkersRequest {
+    /// Construct a new `MarkersRequest` object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetch the marker of the home timeline.
+    pub fn home(mut self) -> Self {
+        self.home = true;
+        self
+    }
+
+    /// Fetch the marker of the notifications.
+    pub fn notifications(mut self) -> Self {
+        self.notifications = true;
+        self
+    }
+
+    /// Serialize into a query string
+    pub fn to_querystring(&self) -> String {
+        let mut opts = vec![];
+
+        if self.home {
+            opts.push("timeline[]=home");
+        }
+
+        if self.notifications {
+            opts.push("timeline[]=notifications");
+        }
+
+        if opts.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", opts.join("&"))
+        }
+    }
+}
+
+/// The read positions to save with `Mastodon::set_markers`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate mammut;
+/// # use mammut::MarkersUpdate;
+/// let update = MarkersUpdate::new().home("103206804533200177");
+/// assert_eq!(
+///     update.to_form(),
+///     vec![(
+///         "home[last_read_id]".to_owned(),
+///         "103206804533200177".to_owned()
+///     )]
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MarkersUpdate<'a> {
+    home: Option<Cow<'a, str>>,
+    notifications: Option<Cow<'a, str>>,
+}
+
+impl<'a> MarkersUpdate<'a> {
+    /// Construct a new `MarkersUpdate` object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the ID of the last read status in the home timeline.
+    pub fn home<S: Into<Cow<'a, str>>>(mut self, last_read_id: S) -> Self {
+        self.home = Some(last_read_id.into());
+        self
+    }
+
+    /// Set the ID of the last read notification.
+    pub fn notifications<S: Into<Cow<'a, str>>>(mut self, last_read_id: S) -> Self {
+        self.notifications = Some(last_read_id.into());
+        self
+    }
+
+    /// Serialize into the fields of a form
+    pub fn to_form(&self) -> Vec<(String, String)> {
+        let mut form = vec![];
+
+        if let Some(ref id) = self.home {
+            form.push(("home[last_read_id]".to_owned(), id.to_string()));
+        }
+
+        if let Some(ref id) = self.notifications {
+            form.push(("notifications[last_read_id]".to_owned(), id.to_string()));
+        }
+
+        form
+    }
+}
+
+/// Options for fetching notifications with `Mastodon::notifications_v2`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate mammut;
+/// # use mammut::{ExpandAccounts, NotificationsRequest};
+/// use mammut::entities::notification::NotificationType;
+///
+/// let request = NotificationsRequest::new()
+///     .types(vec![NotificationType::Mention, NotificationType::Favourite])
+///     .expand_accounts(ExpandAccounts::PartialAvatars)
+///     .limit(20);
+/// # assert_eq!(
+/// #     &request.to_querystring()[..],
+/// #     "?limit=20&types%5B%5D=mention&types%5B%5D=favourite&expand_accounts=partial_avatars"
+/// # );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct NotificationsRequest<'a> {
+    max_id: Option<Cow<'a, str>>,
+    since_id: Option<Cow<'a, str>>,
+    min_id: Option<Cow<'a, str>>,
+    limit: Option<usize>,
+    types: Vec<NotificationType>,
+    exclude_types: Vec<NotificationType>,
+    grouped_types: Vec<NotificationType>,
+    account_id: Option<Cow<'a, str>>,
+    expand_accounts: Option<ExpandAccounts>,
+}
+
+/// How the accounts referenced by notification groups are returned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExpandAccounts {
+    /// Return every account in full.
+    Full,
+    /// Return only the parts of the accounts needed to show their avatars,
+    /// apart from the most recent account of each group.
+    PartialAvatars,
+}
+
+impl<'a> NotificationsRequest<'a> {
+    /// Construct a new `NotificationsRequest` object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the `?max_id=:max_id` flag for the .notifications_v2() request
+    pub fn max_id<S: Into<Cow<'a, str>>>(mut self, max_id: S) -> Self {
+        self.max_id = Some(max_id.into());
+        self
+    }
+
+    /// Set the `?since_id=:since_id` flag for the .notifications_v2() request
+    pub fn since_id<S: Into<Cow<'a, str>>>(mut self, since_id: S) -> Self {
+        self.since_id = Some(since_id.into());
+        self
+    }
+
+    /// Set the `?min_id=:min_id` flag for the .notifications_v2() request
+    pub fn min_id<S: Into<Cow<'a, str>>>(mut self, min_id: S) -> Self {
+        self.min_id = Some(min_id.into());
+        self
+    }
+
+    /// Set the `?limit=:limit` flag for the .notifications_v2() request,
+    /// clamped to `MAX_ACCOUNTS_LIMIT`.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit.min(MAX_ACCOUNTS_LIMIT as usize));
+        self
+    }
+
+    /// Only return notifications of these types.
+    pub fn types(mut self, types: Vec<NotificationType>) -> Self {
+        self.types = types;
+        self
+    }
+
+    /// Don't return notifications of these types.
+    pub fn exclude_types(mut self, exclude_types: Vec<NotificationType>) -> Self {
+        self.exclude_types = exclude_types;
+        self
+    }
+
+    /// Only group notifications of these types. Ignored by the v1 API.
+    pub fn grouped_types(mut self, grouped_types: Vec<NotificationType>) -> Self {
+        self.grouped_types = grouped_types;
+        self
+    }
+
+    /// Only return notifications caused by this account.
+    pub fn account_id<S: Into<Cow<'a, str>>>(mut self, account_id: S) -> Self {
+        self.account_id = Some(account_id.into());
+        self
+    }
+
+    /// Set how referenced accounts are returned. Ignored by the v1 API.
+    pub fn expand_accounts(mut self, expand_accounts: ExpandAccounts) -> Self {
+        self.expand_accounts = Some(expand_accounts);
+        self
+    }
+
+    fn pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
+
+        if let Some(ref max_id) = self.max_id {
+            pairs.push(("max_id", max_id.to_string()));
+        }
+
+        if let Some(ref since_id) = self.since_id {
+            pairs.push(("since_id", since_id.to_string()));
+        }
+
+        if let Some(ref min_id) = self.min_id {
+            pairs.push(("min_id", min_id.to_string()));
+        }
+
+        if let Some(limit) = self.limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+
+        for kind in &self.types {
+            pairs.push(("types[]", kind.as_str().to_owned()));
+        }
+
+        for kind in &self.exclude_types {
+            pairs.push(("exclude_types[]", kind.as_str().to_owned()));
+        }
+
+        for kind in &self.grouped_types {
+            pairs.push(("grouped_types[]", kind.as_str().to_owned()));
+        }
+
+        if let Some(ref account_id) = self.account_id {
+            pairs.push(("account_id", account_id.to_string()));
+        }
+
+        match self.expand_accounts {
+            Some(ExpandAccounts::Full) => pairs.push(("expand_accounts", "full".into())),
+            Some(ExpandAccounts::PartialAvatars) => {
+                pairs.push(("expand_accounts", "partial_avatars".into()))
+            }
+            None => {}
+        }
+
+        pairs
+    }
+
+    /// Serialize into a query string
+    pub fn to_querystring(&self) -> String {
+        let pairs = self.pairs();
+
+        if pairs.is_empty() {
+            String::new()
+        } else {
+            let query = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(pairs)
+                .finish();
+            format!("?{}", query)
+        }
+    }
+}
+
+impl Mastodon {
+    fn from_registration<I>(
+        base: I,
+        client_id: I,
+        client_secret: I,
+        redirect: I,
+        token: I,
+        token_kind: TokenKind,
+        client: Client,
+    ) -> Self
+    where
         I: Into<Cow<'static, str>>,
     {
         let data = Data {
@@ -464,216 +1780,1207 @@ impl Mastodon {
             client_secret: client_secret.into(),
             redirect: redirect.into(),
             token: token.into(),
+            token_kind,
+        };
+
+        let mut headers = HeaderMap::new();
+        let auth = auth_header(&data.token);
+        headers.insert(header::AUTHORIZATION, auth.unwrap());
+
+        Mastodon {
+            client,
+            headers,
+            me: Arc::default(),
+            rate_limit: Arc::default(),
+            default_visibility: None,
+            default_language: None,
+            data,
+        }
+    }
+
+    /// Start building a mastodon instance from the data struct, to set
+    /// options such as the user agent.
+    pub fn builder(data: Data) -> MastodonBuilder {
+        MastodonBuilder {
+            data,
+            user_agent: None,
+        }
+    }
+
+    /// Creates a mastodon instance from the data struct. `data.base` is used
+    /// as is, see `try_from_data` to check it first.
+    ///
+    /// # Panics
+    /// If `data.token` can't be sent in a header, such as when it contains a
+    /// newline. `from_data_validated` returns an error instead.
+    pub fn from_data(data: Data) -> Self {
+        let mut headers = HeaderMap::new();
+        let auth = auth_header(&data.token);
+        headers.insert(header::AUTHORIZATION, auth.unwrap());
+
+        Mastodon {
+            client: Client::new(),
+            headers,
+            me: Arc::default(),
+            rate_limit: Arc::default(),
+            default_visibility: None,
+            default_language: None,
+            data,
+        }
+    }
+
+    /// Creates a mastodon instance from the data struct, after checking that
+    /// `data.base` is a valid url and removing any trailing slashes from it.
+    ///
+    /// # Errors
+    /// If `data.base` isn't an absolute url, such as when it is missing the
+    /// scheme, an `Error::Url` is returned.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # fn main() -> mammut::Result<()> {
+    /// use mammut::{Data, Mastodon};
+    ///
+    /// let mut data = Data {
+    ///     base: "https://mastodon.social/".into(),
+    ///     client_id: "id".into(),
+    ///     client_secret: "secret".into(),
+    ///     redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+    ///     token: "token".into(),
+    ///     token_kind: Default::default(),
+    /// };
+    ///
+    /// let client = Mastodon::try_from_data(data.clone())?;
+    /// assert_eq!(client.base, "https://mastodon.social");
+    ///
+    /// data.base = "mastodon.social".into();
+    /// assert!(Mastodon::try_from_data(data).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_from_data(mut data: Data) -> Result<Self> {
+        let url = Url::parse(&data.base)?;
+
+        if url.cannot_be_a_base() {
+            return Err(Error::Url(UrlError::RelativeUrlWithoutBase));
+        }
+
+        data.base = url.as_str().trim_end_matches('/').to_owned().into();
+
+        Ok(Self::from_data(data))
+    }
+
+    /// Creates a mastodon instance from the data struct like `try_from_data`,
+    /// after checking there is an access token that can be sent.
+    ///
+    /// # Errors
+    /// If `data.token` is empty, an `Error::AccessTokenRequired` is returned,
+    /// if it can't be sent in a header, such as when it contains a newline,
+    /// an `Error::InvalidHeaderValue`, and if `data.base` isn't an absolute
+    /// url an `Error::Url`.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// use mammut::{Data, Error, Mastodon};
+    ///
+    /// let data = Data {
+    ///     base: "https://mastodon.social".into(),
+    ///     client_id: "id".into(),
+    ///     client_secret: "secret".into(),
+    ///     redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+    ///     token: "".into(),
+    ///     token_kind: Default::default(),
+    /// };
+    ///
+    /// match Mastodon::from_data_validated(data.clone()) {
+    ///     Err(Error::AccessTokenRequired) => {}
+    ///     _ => panic!("an empty token should be rejected"),
+    /// }
+    ///
+    /// let data = Data {
+    ///     token: "token\n".into(),
+    ///     ..data
+    /// };
+    /// match Mastodon::from_data_validated(data) {
+    ///     Err(Error::InvalidHeaderValue(_)) => {}
+    ///     _ => panic!("a token with a newline should be rejected"),
+    /// }
+    /// ```
+    pub fn from_data_validated(data: Data) -> Result<Self> {
+        if data.token.trim().is_empty() {
+            return Err(Error::AccessTokenRequired);
+        }
+
+        auth_header(&data.token)?;
+
+        Self::try_from_data(data)
+    }
+
+    paged_routes! {
+        (get) favourites: "favourites" => [Status; MAX_STATUSES_LIMIT],
+        (get) blocks: "blocks" => [Account; MAX_ACCOUNTS_LIMIT],
+        (get) domain_blocks: "domain_blocks" => [String; MAX_ACCOUNTS_LIMIT],
+        (get) follow_requests: "follow_requests" => [Account; MAX_ACCOUNTS_LIMIT],
+        (get) mutes: "mutes" => [Account; MAX_ACCOUNTS_LIMIT],
+        (get) notifications: "notifications" => [Notification; MAX_ACCOUNTS_LIMIT],
+        (get) notification_requests: "notifications/requests" => [NotificationRequest; MAX_ACCOUNTS_LIMIT],
+        (get) followed_tags: "followed_tags" => [Tag; MAX_ACCOUNTS_LIMIT],
+    }
+
+    paged_routes_with_id! {
+        (get) followers: "accounts/{}/followers" => [Account; MAX_ACCOUNTS_LIMIT],
+        (get) following: "accounts/{}/following" => [Account; MAX_ACCOUNTS_LIMIT],
+        (get) reblogged_by: "statuses/{}/reblogged_by" => [Account; MAX_ACCOUNTS_LIMIT],
+        (get) favourited_by: "statuses/{}/favourited_by" => [Account; MAX_ACCOUNTS_LIMIT],
+    }
+
+    route! {
+        (get) instance: "instance" => Instance,
+        (get) lists: "lists" => Vec<List>,
+        (get) filters: "filters" => Vec<Filter>,
+        (get) verify_credentials: "accounts/verify_credentials" => Account,
+        (post (id: &str,)) authorize_follow_request: "accounts/follow_requests/authorize" => Empty,
+        (post (id: &str,)) reject_follow_request: "accounts/follow_requests/reject" => Empty,
+        (post (title: &str, replies_policy: Option<RepliesPolicy>, exclusive: Option<bool>,)) new_list: "lists" => List,
+        (post (phrase: &str, context: &[FilterContext], irreversible: Option<bool>, whole_word: Option<bool>, expires_in: Option<u64>,)) new_filter: "filters" => Filter,
+        (post) clear_notifications: "notifications/clear" => Empty,
+        (post (id: &[&str],)) accept_notification_requests: "notifications/requests/accept" => Empty,
+        (post (id: &[&str],)) dismiss_notification_requests: "notifications/requests/dismiss" => Empty,
+        (get) notification_policy: "notifications/policy" => NotificationPolicy,
+        (get) featured_tags: "featured_tags" => Vec<FeaturedTag>,
+        (get) featured_tag_suggestions: "featured_tags/suggestions" => Vec<Tag>,
+        (patch (filter_not_following: Option<bool>, filter_not_followers: Option<bool>, filter_new_accounts: Option<bool>, filter_private_mentions: Option<bool>,)) update_notification_policy: "notifications/policy" => NotificationPolicy,
+    }
+
+    route_id! {
+        (get) get_account: "accounts/{}" => Account,
+        (post) follow: "accounts/{}/follow" => Account,
+        (post) unfollow: "accounts/{}/unfollow" => Account,
+        (get) block: "accounts/{}/block" => Account,
+        (get) unblock: "accounts/{}/unblock" => Account,
+        (get) mute: "accounts/{}/mute" => Account,
+        (get) unmute: "accounts/{}/unmute" => Account,
+        (post) remove_follower: "accounts/{}/remove_from_followers" => Relationship,
+        (get) get_notification: "notifications/{}" => Notification,
+        (get) get_notification_request: "notifications/requests/{}" => NotificationRequest,
+        (post) accept_notification_request: "notifications/requests/{}/accept" => Empty,
+        (post) dismiss_notification_request: "notifications/requests/{}/dismiss" => Empty,
+        (get) get_status: "statuses/{}" => Status,
+        (get) get_context: "statuses/{}/context" => Context,
+        (get) get_status_source: "statuses/{}/source" => StatusSource,
+        (get) get_status_history: "statuses/{}/history" => Vec<StatusEdit>,
+        (get) get_card: "statuses/{}/card" => Card,
+        (post) reblog: "statuses/{}/reblog" => Status,
+        (post) unreblog: "statuses/{}/unreblog" => Status,
+        (post) favourite: "statuses/{}/favourite" => Status,
+        (post) unfavourite: "statuses/{}/unfavourite" => Status,
+        (delete) delete_status: "statuses/{}" => Empty,
+        (get) account_lists: "accounts/{}/lists" => Vec<List>,
+        (get) get_list: "lists/{}" => List,
+        (put (title: &str, replies_policy: Option<RepliesPolicy>, exclusive: Option<bool>,)) update_list: "lists/{}" => List,
+        (delete) delete_list: "lists/{}" => Empty,
+        (post (account_ids: &[&str],)) add_to_list: "lists/{}/accounts" => Empty,
+        (delete (account_ids: &[&str],)) remove_from_list: "lists/{}/accounts" => Empty,
+        (get) get_filter: "filters/{}" => Filter,
+        (put (phrase: &str, context: &[FilterContext], irreversible: Option<bool>, whole_word: Option<bool>, expires_in: Option<u64>,)) update_filter: "filters/{}" => Filter,
+        (delete) delete_filter: "filters/{}" => Empty,
+        (delete) unfeature_tag: "featured_tags/{}" => Empty,
+    }
+
+    route_v2! {
+        (get) instance_v2: "instance" => InstanceV2,
+        (get) filters_v2: "filters" => Vec<FilterV2>,
+    }
+
+    route_v2_id! {
+        (get) get_filter_v2: "filters/{}" => FilterV2,
+        (delete) delete_filter_v2: "filters/{}" => Empty,
+        (get) filter_keywords: "filters/{}/keywords" => Vec<FilterKeyword>,
+        (post (keyword: &str, whole_word: Option<bool>,)) add_filter_keyword: "filters/{}/keywords" => FilterKeyword,
+        (get) get_filter_keyword: "filters/keywords/{}" => FilterKeyword,
+        (put (keyword: &str, whole_word: Option<bool>,)) update_filter_keyword: "filters/keywords/{}" => FilterKeyword,
+        (delete) delete_filter_keyword: "filters/keywords/{}" => Empty,
+        (get) filter_statuses: "filters/{}/statuses" => Vec<FilterStatus>,
+        (post (status_id: &str,)) add_filter_status: "filters/{}/statuses" => FilterStatus,
+        (get) get_filter_status: "filters/statuses/{}" => FilterStatus,
+        (delete) delete_filter_status: "filters/statuses/{}" => Empty,
+    }
+
+    /// Create a filter with the v2 filters API, along with its keywords.
+    pub fn new_filter_v2(
+        &self,
+        title: &str,
+        context: &[FilterContext],
+        filter_action: Option<FilterAction>,
+        expires_in: Option<u64>,
+        keywords: &[FilterKeywordAttributes<'_>],
+    ) -> Result<FilterV2> {
+        let form_data = filter_v2_form(title, context, filter_action, expires_in, keywords);
+
+        let response = self.send(
+            self.client
+                .post(&self.route("/api/v2/filters"))
+                .headers(self.headers.clone())
+                .form(&form_data),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Update a filter with the v2 filters API. Keywords with an `id` change
+    /// or remove the existing keyword, and those without one are added.
+    pub fn update_filter_v2(
+        &self,
+        id: &str,
+        title: &str,
+        context: &[FilterContext],
+        filter_action: Option<FilterAction>,
+        expires_in: Option<u64>,
+        keywords: &[FilterKeywordAttributes<'_>],
+    ) -> Result<FilterV2> {
+        let form_data = filter_v2_form(title, context, filter_action, expires_in, keywords);
+
+        let response = self.send(
+            self.client
+                .put(&self.route(&format!("/api/v2/filters/{}", id)))
+                .headers(self.headers.clone())
+                .form(&form_data),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Returns the account of the authenticated user. The account is fetched
+    /// with `verify_credentials` on the first call and cached afterwards, use
+    /// `refresh_me` to fetch it again.
+    pub fn me(&self) -> Result<Account> {
+        if let Some(ref account) = *self.me.read().unwrap() {
+            return Ok(account.clone());
+        }
+
+        self.refresh_me()
+    }
+
+    /// Fetches the account of the authenticated user from the instance,
+    /// replacing the copy cached by `me`.
+    pub fn refresh_me(&self) -> Result<Account> {
+        let account = self.verify_credentials()?;
+        *self.me.write().unwrap() = Some(account.clone());
+
+        Ok(account)
+    }
+
+    /// Dismiss a single notification. Instances older than 3.1 don't have
+    /// `/api/v1/notifications/:id/dismiss`, so if it isn't found the request
+    /// is retried with `/api/v1/notifications/dismiss`.
+    pub fn dismiss_notification(&self, id: &str) -> Result<Empty> {
+        let url = self.route(&format!("/api/v1/notifications/{}/dismiss", id));
+        let response = self.send(self.client.post(&url).headers(self.headers.clone()))?;
+
+        if response.status() != StatusCode::NOT_FOUND {
+            return deserialise(response);
+        }
+
+        let response = self.send(
+            self.client
+                .post(&self.route("/api/v1/notifications/dismiss"))
+                .headers(self.headers.clone())
+                .form(&[("id", id)]),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Get the user's notifications grouped together, such as everyone who
+    /// favourited a status. Instances older than 4.3 don't have
+    /// `/api/v2/notifications`, so if it isn't found the notifications are
+    /// fetched from `/api/v1/notifications` instead, each in a group of its
+    /// own.
+    ///
+    /// Use `GroupedNotificationsResults::hydrated_groups` to get the accounts
+    /// and statuses of each group.
+    pub fn notifications_v2<'a, R>(&self, request: R) -> Result<GroupedNotificationsResults>
+    where
+        R: Into<Option<NotificationsRequest<'a>>>,
+    {
+        let query = request
+            .into()
+            .map(|request| request.to_querystring())
+            .unwrap_or_default();
+
+        let url = format!("{}/api/v2/notifications{}", self.base, query);
+        let response = self.send(self.client.get(&url).headers(self.headers.clone()))?;
+
+        if response.status() != StatusCode::NOT_FOUND {
+            return deserialise(response);
+        }
+
+        let url = format!("{}/api/v1/notifications{}", self.base, query);
+        let response = self.send(self.client.get(&url).headers(self.headers.clone()))?;
+
+        deserialise::<Vec<Notification>>(response).map(GroupedNotificationsResults::from)
+    }
+
+    /// Get the user's read positions in the timelines of `request`.
+    pub fn get_markers(&self, request: MarkersRequest) -> Result<Markers> {
+        let url = format!("{}/api/v1/markers{}", self.base, request.to_querystring());
+
+        self.get(url)
+    }
+
+    /// Save the user's read positions, returning every marker that was set.
+    ///
+    /// # Errors
+    /// If another client updated a marker at the same time the server
+    /// answers with a conflict, an error whose `Error::status` is
+    /// `Some(StatusCode::CONFLICT)`, and the update can be retried.
+    pub fn set_markers(&self, update: MarkersUpdate<'_>) -> Result<Markers> {
+        let response = self.send(
+            self.client
+                .post(&self.route("/api/v1/markers"))
+                .headers(self.headers.clone())
+                .form(&update.to_form()),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Check that the app's access token is still valid, returning the app.
+    /// Works with both a user's token and an app token.
+    ///
+    /// # Errors
+    /// If the token, or the app it was created for, is no longer valid the
+    /// server answers with an error for which `Error::is_unauthorized` is
+    /// true, and the app needs to be registered or authorised again.
+    pub fn verify_app_credentials(&self) -> Result<Application> {
+        let response = self.send(
+            self.client
+                .get(&self.route("/api/v1/apps/verify_credentials"))
+                .headers(self.headers.clone()),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Check whether the access token is still valid, such as when starting
+    /// up, without changing anything on the instance. For a user's token the
+    /// account is also cached for `me`.
+    ///
+    /// # Errors
+    /// A refused token isn't an error, but `AuthStatus::Invalid`. Errors are
+    /// returned when the instance couldn't say, such as when it can't be
+    /// reached, which `Error::is_connect_error` tells apart.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// # let data = Data {
+    /// #   base: "".into(),
+    /// #   client_id: "".into(),
+    /// #   client_secret: "".into(),
+    /// #   redirect: "".into(),
+    /// #   token: "".into(),
+    /// #   token_kind: Default::default(),
+    /// # };
+    /// use mammut::AuthStatus;
+    ///
+    /// let client = Mastodon::from_data(data);
+    /// match client.check_auth() {
+    ///     Ok(AuthStatus::Invalid) => println!("Please log in again"),
+    ///     Ok(_) => println!("Logged in"),
+    ///     Err(ref e) if e.is_connect_error() => println!("Offline"),
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn check_auth(&self) -> Result<AuthStatus> {
+        if self.data.token.trim().is_empty() {
+            return Ok(AuthStatus::Invalid);
+        }
+
+        let status = match self.data.token_kind {
+            TokenKind::User => self
+                .refresh_me()
+                .map(|account| AuthStatus::User(Box::new(account))),
+            TokenKind::App => self.verify_app_credentials().map(AuthStatus::App),
         };
 
-        let mut headers = HeaderMap::new();
-        let auth = HeaderValue::from_str(&format!("Bearer {}", data.token));
-        headers.insert(header::AUTHORIZATION, auth.unwrap());
+        match status {
+            Err(ref e) if e.status() == Some(StatusCode::UNAUTHORIZED) => Ok(AuthStatus::Invalid),
+            status => status,
+        }
+    }
 
-        Mastodon {
-            client: client,
-            headers: headers,
-            data: data,
+    /// Update the user's credentials, such as their display name or avatar.
+    pub fn update_credentials(&self, changes: CredientialsBuilder) -> Result<Account> {
+        let url = self.route("/api/v1/accounts/update_credentials");
+        let response = self.send(
+            self.client
+                .patch(&url)
+                .headers(self.headers.clone())
+                .multipart(changes.into_form()?),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Revokes the access token with the instance, so that it, and any saved
+    /// copies of `Data` containing it, can no longer be used. Consumes the
+    /// client as it is unusable afterwards.
+    ///
+    /// Revoking a token that was already revoked succeeds.
+    ///
+    /// # Errors
+    /// If the client id, client secret or access token is empty, an
+    /// `Error::ClientIdRequired`, `Error::ClientSecretRequired` or
+    /// `Error::AccessTokenRequired` is returned without contacting the
+    /// instance. If the instance refuses, such as when the client id and
+    /// secret don't match, its reason is returned as an `Error::Api`.
+    pub fn revoke_token(self) -> Result<()> {
+        if self.client_id.trim().is_empty() {
+            return Err(Error::ClientIdRequired);
+        } else if self.client_secret.trim().is_empty() {
+            return Err(Error::ClientSecretRequired);
+        } else if self.token.trim().is_empty() {
+            return Err(Error::AccessTokenRequired);
+        }
+
+        let form_data = json!({
+            "client_id": self.client_id,
+            "client_secret": self.client_secret,
+            "token": self.token,
+        });
+
+        let response = self.send(
+            self.client
+                .post(&self.route("/oauth/revoke"))
+                .headers(self.headers_without_token())
+                .form(&form_data),
+        )?;
+
+        // Success is an empty object, or on some versions no body at all.
+        if response.status().is_success() {
+            return Ok(());
         }
+
+        deserialise::<Empty>(response).map(|_| ())
     }
 
-    /// Creates a mastodon instance from the data struct.
-    pub fn from_data(data: Data) -> Self {
-        let mut headers = HeaderMap::new();
-        let auth = HeaderValue::from_str(&format!("Bearer {}", data.token));
-        headers.insert(header::AUTHORIZATION, auth.unwrap());
+    /// Reblog each of the statuses with the IDs in `ids`, carrying on after
+    /// any that fail, such as deleted statuses. Returns the result for each
+    /// ID, in the same order.
+    ///
+    /// Once the instance answers that the rate limit was reached, the rest
+    /// of the statuses aren't sent, and are returned as `Error::NotSent`.
+    pub fn reblog_many<'a>(&self, ids: &[&'a str]) -> Vec<(&'a str, Result<Status>)> {
+        self.for_each_status(ids, Mastodon::reblog)
+    }
 
-        Mastodon {
-            client: Client::new(),
-            headers: headers,
-            data: data,
+    /// Favourite each of the statuses with the IDs in `ids`, in the same way
+    /// as `reblog_many`.
+    pub fn favourite_many<'a>(&self, ids: &[&'a str]) -> Vec<(&'a str, Result<Status>)> {
+        self.for_each_status(ids, Mastodon::favourite)
+    }
+
+    // Call `action` with each ID, stopping early when rate limited.
+    fn for_each_status<'a>(
+        &self,
+        ids: &[&'a str],
+        action: fn(&Mastodon, &str) -> Result<Status>,
+    ) -> Vec<(&'a str, Result<Status>)> {
+        let mut rate_limited = false;
+
+        ids.iter()
+            .map(|&id| {
+                if rate_limited {
+                    return (id, Err(Error::NotSent));
+                }
+
+                let result = action(self, id);
+                rate_limited = result.as_ref().err().map_or(false, Error::is_rate_limited);
+
+                (id, result)
+            })
+            .collect()
+    }
+
+    /// Set the visibility of statuses posted with `new_status` when their
+    /// `StatusBuilder` doesn't set one.
+    pub fn set_default_visibility(&mut self, visibility: Visibility) {
+        self.default_visibility = Some(visibility);
+    }
+
+    /// Set the language of statuses posted with `new_status` when their
+    /// `StatusBuilder` doesn't set one, such as `en`.
+    pub fn set_default_language<I: Into<String>>(&mut self, language: I) {
+        self.default_language = Some(language.into());
+    }
+
+    /// Post a new status to the account. The defaults set with
+    /// `set_default_visibility` and `set_default_language` are used for the
+    /// options `status` doesn't set.
+    ///
+    /// If `status` has an `idempotency_key`, it is sent as the
+    /// `Idempotency-Key` header, so retrying with the same key returns the
+    /// status that was already posted rather than posting it twice.
+    pub fn new_status(&self, status: StatusBuilder) -> Result<Status> {
+        let mut headers = self.headers.clone();
+
+        if let Some(ref key) = status.idempotency_key {
+            headers.insert("Idempotency-Key", HeaderValue::from_str(key)?);
+        }
+
+        let response = self.send(
+            self.client
+                .post(&self.route("/api/v1/statuses"))
+                .headers(headers)
+                .json(&self.with_status_defaults(status)),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// The JSON body `new_status` would send to post `status`, including the
+    /// defaults, without contacting the instance. Useful for checking what a
+    /// status will be posted as, or for asserting on it in tests.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # fn main() -> mammut::Result<()> {
+    /// use mammut::{status_builder::Visibility, Data, Mastodon, StatusBuilder};
+    ///
+    /// let data = Data {
+    ///     base: "https://mastodon.social".into(),
+    ///     client_id: "id".into(),
+    ///     client_secret: "secret".into(),
+    ///     redirect: "urn:ietf:wg:oauth:2.0:oob".into(),
+    ///     token: "token".into(),
+    ///     token_kind: Default::default(),
+    /// };
+    ///
+    /// let mut client = Mastodon::from_data(data);
+    /// client.set_default_visibility(Visibility::Unlisted);
+    ///
+    /// let body = client.preview_status(&StatusBuilder::new("Hello!".into()))?;
+    /// assert_eq!(body["status"], "Hello!");
+    /// assert_eq!(body["visibility"], "unlisted");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn preview_status(&self, status: &StatusBuilder) -> Result<json::Value> {
+        Ok(json::to_value(self.with_status_defaults(status.clone()))?)
+    }
+
+    // Fill in the options `status` doesn't set with the client's defaults.
+    fn with_status_defaults(&self, mut status: StatusBuilder) -> StatusBuilder {
+        if status.visibility.is_none() {
+            status.visibility = self.default_visibility;
+        }
+
+        if status.language.is_none() {
+            status.language = self.default_language.clone();
+        }
+
+        status
+    }
+
+    /// Get the timeline of statuses from the accounts the user follows. Only
+    /// the `max_id`, `since_id`, `min_id` and `limit` options of the request
+    /// are used.
+    pub fn get_home_timeline<'a, S>(&self, request: S) -> Result<Page<'_, Status>>
+    where
+        S: Into<Option<StatusesRequest<'a>>>,
+    {
+        let mut url = self.route("/api/v1/timelines/home");
+
+        if let Some(request) = request.into() {
+            url += &request.to_querystring();
+        }
+
+        let response = self.send(self.client.get(&url).headers(self.headers.clone()))?;
+
+        Page::new(self, response)
+    }
+
+    /// Edit one of the user's statuses, replacing its text, spoiler text,
+    /// media and sensitivity. Its visibility and the status it replies to
+    /// can't be changed, so those fields of `status` are ignored. Use
+    /// `get_status_source` to get the text to edit.
+    pub fn edit_status(&self, id: &str, status: StatusBuilder) -> Result<Status> {
+        let response = self.send(
+            self.client
+                .put(&self.route(&format!("/api/v1/statuses/{}", id)))
+                .headers(self.headers.clone())
+                .json(&status),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Get the federated timeline for the instance. Only the `only_media`,
+    /// `max_id`, `since_id`, `min_id` and `limit` options of the request are
+    /// used.
+    pub fn get_public_timeline<'a, S>(&self, local: bool, request: S) -> Result<Vec<Status>>
+    where
+        S: Into<Option<StatusesRequest<'a>>>,
+    {
+        let url = self.route("/api/v1/timelines/public");
+
+        self.get(timeline_url(url, local, request.into())?)
+    }
+
+    /// Get the federated timeline for the instance, with the options of
+    /// `request`, such as only the statuses from other instances.
+    pub fn get_public_timeline_with(
+        &self,
+        request: PublicTimelineRequest<'_>,
+    ) -> Result<Vec<Status>> {
+        let url = Url::parse_with_params(&self.route("/api/v1/timelines/public"), request.pairs())?;
+
+        self.get(url.into_string())
+    }
+
+    /// Get timeline filtered by a hashtag(eg. `#coffee`) either locally or
+    /// federated. The hashtag is url encoded, and the `#` is optional. Only
+    /// the `only_media`, `max_id`, `since_id`, `min_id` and `limit` options
+    /// of the request are used.
+    pub fn get_tagged_timeline<'a, S>(
+        &self,
+        hashtag: String,
+        local: bool,
+        request: S,
+    ) -> Result<Vec<Status>>
+    where
+        S: Into<Option<StatusesRequest<'a>>>,
+    {
+        let url = self.tag_url("/api/v1/timelines/tag/", &hashtag, "");
+
+        self.get(timeline_url(url, local, request.into())?)
+    }
+
+    /// Get statuses of a single account by id. Optionally only with pictures
+    /// and or excluding replies.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// # let data = Data {
+    /// #   base: "".into(),
+    /// #   client_id: "".into(),
+    /// #   client_secret: "".into(),
+    /// #   redirect: "".into(),
+    /// #   token: "".into(),
+    /// #   token_kind: Default::default(),
+    /// # };
+    /// let client = Mastodon::from_data(data);
+    /// let statuses = client.statuses("user-id", None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # use mammut::{Data, Mastodon, StatusesRequest};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<Error>> {
+    /// # let data = Data {
+    /// #   base: "".into(),
+    /// #   client_id: "".into(),
+    /// #   client_secret: "".into(),
+    /// #   redirect: "".into(),
+    /// #   token: "".into(),
+    /// #   token_kind: Default::default(),
+    /// # };
+    /// let client = Mastodon::from_data(data);
+    /// let request = StatusesRequest::default()
+    ///                               .only_media();
+    /// let statuses = client.statuses("user-id", request)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn statuses<'a, S>(&self, id: &str, request: S) -> Result<Page<'_, Status>>
+    where
+        S: Into<Option<StatusesRequest<'a>>>,
+    {
+        let mut url = format!("{}/api/v1/accounts/{}/statuses", self.base, id);
+
+        if let Some(request) = request.into() {
+            url = format!("{}{}", url, request.to_querystring());
+        }
+
+        let response = self.send(self.client.get(&url).headers(self.headers.clone()))?;
+
+        Page::new(self, response)
+    }
+
+    /// Get the timeline of statuses from the accounts in a list. Only the
+    /// `max_id`, `since_id`, `min_id` and `limit` options of the request are
+    /// used.
+    pub fn get_list_timeline<'a, S>(&self, id: &str, request: S) -> Result<Page<'_, Status>>
+    where
+        S: Into<Option<StatusesRequest<'a>>>,
+    {
+        let mut url = format!("{}/api/v1/timelines/list/{}", self.base, id);
+
+        if let Some(request) = request.into() {
+            url = format!("{}{}", url, request.to_querystring());
+        }
+
+        let response = self.send(self.client.get(&url).headers(self.headers.clone()))?;
+
+        Page::new(self, response)
+    }
+
+    /// Returns the client account's relationship to a list of other accounts.
+    /// Such as whether they follow them or vice versa.
+    pub fn relationships(&self, ids: &[&str]) -> Result<Page<'_, Relationship>> {
+        let url = Url::parse_with_params(
+            &self.route("/api/v1/accounts/relationships"),
+            ids.iter().map(|id| ("id[]", id)),
+        )?;
+
+        let response = self.send(self.client.get(url).headers(self.headers.clone()))?;
+
+        Page::new(self, response)
+    }
+
+    /// Remove the statuses posted or reblogged by accounts that
+    /// `relationships` say the user blocks or mutes, or whose domain they
+    /// block. The server already leaves most of these out, but not all of
+    /// them, such as reblogs of muted accounts. No requests are made.
+    pub fn filter_blocked_statuses(
+        statuses: Vec<Status>,
+        relationships: &[Relationship],
+    ) -> Vec<Status> {
+        let hidden = |account: &Account| {
+            relationships
+                .iter()
+                .any(|relationship| relationship.id == account.id && relationship.hides_statuses())
+        };
+
+        statuses
+            .into_iter()
+            .filter(|status| {
+                !hidden(&status.account)
+                    && !status
+                        .reblog
+                        .as_ref()
+                        .map_or(false, |reblog| hidden(&reblog.account))
+            })
+            .collect()
+    }
+
+    /// Returns the client account's relationship to a single other account,
+    /// or `None` if the instance doesn't return one, such as when the account
+    /// doesn't exist.
+    pub fn relationship(&self, id: &str) -> Result<Option<Relationship>> {
+        Ok(self.relationships(&[id])?.initial_items.into_iter().next())
+    }
+
+    /// Get an account by its `username` or `username@domain` handle. Unlike
+    /// `search_accounts` this returns only the exact account, and an error if
+    /// there isn't one.
+    pub fn lookup_account(&self, acct: &str) -> Result<Account> {
+        let url = Url::parse_with_params(
+            &self.route("/api/v1/accounts/lookup"),
+            &[("acct", acct.trim_start_matches('@'))],
+        )?;
+
+        self.get(url.into_string())
+    }
+
+    /// Follow an account by its `username@domain` handle, fetching it from its
+    /// instance first if this instance doesn't know it yet.
+    ///
+    /// # Errors
+    /// If no account has exactly that handle, an `Error::AccountNotFound` is
+    /// returned.
+    pub fn follow_by_acct(&self, acct: &str) -> Result<Relationship> {
+        let account = self.resolve_acct(acct)?;
+
+        self.post(self.route(&format!("/api/v1/accounts/{}/follow", account.id)))
+    }
+
+    /// Follow a remote account by its handle.
+    #[deprecated(note = "use `follow_by_acct`, `/api/v1/follows` was removed from Mastodon")]
+    pub fn follows(&self, uri: Cow<'static, str>) -> Result<Account> {
+        let account = self.resolve_acct(&uri)?;
+        let url = self.route(&format!("/api/v1/accounts/{}/follow", account.id));
+        self.post::<Relationship>(url)?;
+
+        Ok(account)
+    }
+
+    /// Get the status at `url`, which may be on any instance, fetching it
+    /// from its instance if this instance doesn't know it yet. Returns `None`
+    /// if the url isn't a status this instance can fetch.
+    pub fn resolve_status(&self, url: &str) -> Result<Option<Status>> {
+        let request = SearchRequest::new()
+            .search_type(SearchType::Statuses)
+            .resolve();
+        let mut statuses = self.search_v2(url.trim(), request)?.statuses;
+
+        // Prefer the status with exactly that url, in case the search also
+        // matched statuses that mention it.
+        let exact = statuses
+            .iter()
+            .position(|status| status.uri == url || status.url.as_deref() == Some(url));
+
+        Ok(match exact {
+            Some(index) => Some(statuses.swap_remove(index)),
+            None => statuses.into_iter().next(),
+        })
+    }
+
+    /// Get the account whose profile is at `url`, which may be on any
+    /// instance, fetching it from its instance if this instance doesn't know
+    /// it yet. Returns `None` if the url isn't an account this instance can
+    /// fetch.
+    pub fn resolve_account(&self, url: &str) -> Result<Option<Account>> {
+        let request = SearchRequest::new()
+            .search_type(SearchType::Accounts)
+            .resolve();
+        let mut accounts = self.search_v2(url.trim(), request)?.accounts;
+
+        let exact = accounts.iter().position(|account| account.url == url);
+
+        Ok(match exact {
+            Some(index) => Some(accounts.swap_remove(index)),
+            None => accounts.into_iter().next(),
+        })
+    }
+
+    /// Get the local account with exactly the username `username`, or `None`
+    /// if this instance has no such account. Unlike `search_accounts`, a
+    /// similarly named account is never returned.
+    pub fn find_local_account(&self, username: &str) -> Result<Option<Account>> {
+        let username = username.trim().trim_start_matches('@');
+        // The search is fuzzy, so ask for a few results in case the exact
+        // match isn't ranked first.
+        let url = Url::parse_with_params(
+            &self.route("/api/v1/accounts/search"),
+            &[("q", username), ("resolve", "false"), ("limit", "5")],
+        )?;
+        let accounts: Vec<Account> =
+            deserialise(self.send(self.client.get(url).headers(self.headers.clone()))?)?;
+
+        // Local accounts have an `acct` without a domain.
+        Ok(accounts
+            .into_iter()
+            .find(|account| account.acct.eq_ignore_ascii_case(username)))
+    }
+
+    // Search for an account by its handle, resolving it remotely, and pick
+    // the one with exactly that handle out of the results. The instance
+    // answers a handle it can't find with no results rather than a `404`.
+    fn resolve_acct(&self, acct: &str) -> Result<Account> {
+        let acct = acct.trim().trim_start_matches('@');
+        let local_domain = Url::parse(&self.base)?.host_str().map(str::to_owned);
+        let request = SearchRequest::new()
+            .search_type(SearchType::Accounts)
+            .resolve();
+
+        self.search_v2(acct, request)?
+            .accounts
+            .into_iter()
+            .find(|account| acct_matches(acct, &account.acct, local_domain.as_deref()))
+            .ok_or_else(|| Error::AccountNotFound(acct.to_owned()))
+    }
+
+    /// Returns, for each of the accounts, the accounts the user follows that
+    /// also follow it.
+    pub fn familiar_followers(&self, ids: &[&str]) -> Result<Vec<FamiliarFollowers>> {
+        let url = Url::parse_with_params(
+            &self.route("/api/v1/accounts/familiar_followers"),
+            ids.iter().map(|id| ("id[]", id)),
+        )?;
+
+        self.get(url.into_string())
+    }
+
+    /// Get the custom emojis of the instance. The access token is only sent
+    /// if there is one, so this works with a client that hasn't
+    /// authenticated.
+    pub fn custom_emojis(&self) -> Result<Vec<Emoji>> {
+        deserialise(self.get_public(&self.route("/api/v1/custom_emojis"))?)
+    }
+
+    /// Equivalent to `/api/v1/custom_emojis`, as a `Page`. The instance
+    /// returns all of its emojis at once, so there is only ever one page.
+    #[deprecated(note = "use `custom_emojis`, which also works without a token")]
+    pub fn get_emojis(&self) -> Result<Page<'_, Emoji>> {
+        let url = self.route("/api/v1/custom_emojis");
+        let request = self.client.get(&url).headers(self.headers.clone());
+        let response = self.send(request)?;
+
+        Page::new(self, response)
+    }
+
+    /// Get the limits of the instance that matter when posting, such as the
+    /// maximum number of characters of a status. They are taken from
+    /// `instance_v2`, or from `instance` on instances older than 4.0 and
+    /// those whose v2 instance is missing fields, such as some forks.
+    pub fn limits(&self) -> Result<InstanceLimits> {
+        let response = self.send(
+            self.client
+                .get(&self.route("/api/v2/instance"))
+                .headers(self.headers.clone()),
+        )?;
+
+        if response.status() != StatusCode::NOT_FOUND {
+            match deserialise::<InstanceV2>(response) {
+                Ok(instance) => return Ok(instance.configuration.into()),
+                Err(Error::Deserialize { .. }) => {}
+                Err(e) => return Err(e),
+            }
         }
+
+        self.instance().map(InstanceLimits::from)
+    }
+
+    /// Get the domains of the instances this instance knows about.
+    ///
+    /// Like `custom_emojis` this works without authenticating, but instances
+    /// may require it, which is an `Error::Api` or `Error::Client` with a
+    /// `401`. Instances that don't share their peers answer with a `404`.
+    pub fn instance_peers(&self) -> Result<Vec<String>> {
+        deserialise(self.get_public(&self.route("/api/v1/instance/peers"))?)
+    }
+
+    /// Get the activity on the instance for each of the last twelve weeks,
+    /// most recent first. See `instance_peers` for when this fails.
+    pub fn instance_activity(&self) -> Result<Vec<ActivityWeek>> {
+        deserialise(self.get_public(&self.route("/api/v1/instance/activity"))?)
+    }
+
+    /// Get the rules of the instance. See `instance_peers` for when this
+    /// fails.
+    pub fn instance_rules(&self) -> Result<Vec<Rule>> {
+        deserialise(self.get_public(&self.route("/api/v1/instance/rules"))?)
+    }
+
+    /// Get the extended description of the instance, such as its about page.
+    /// See `instance_peers` for when this fails.
+    pub fn instance_extended_description(&self) -> Result<ExtendedDescription> {
+        deserialise(self.get_public(&self.route("/api/v1/instance/extended_description"))?)
+    }
+
+    /// Get the domains the instance has blocked, if it publishes them.
+    ///
+    /// Instances may only show them to their users, answering with a `401`
+    /// otherwise, or not publish them at all, answering with a `404`.
+    /// `Error::status` tells these apart.
+    pub fn instance_domain_blocks(&self) -> Result<Vec<DomainBlock>> {
+        deserialise(self.get_public(&self.route("/api/v1/instance/domain_blocks"))?)
+    }
+
+    /// Report an account to the moderators of the instance.
+    pub fn report(&self, report: ReportBuilder) -> Result<Report> {
+        let response = self.send(
+            self.client
+                .post(&self.route("/api/v1/reports"))
+                .headers(self.headers.clone())
+                .form(&report.into_form()),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Hide everything from `domain`, and remove the user's followers from it.
+    /// Internationalised domains are sent as punycode.
+    ///
+    /// # Errors
+    /// If `domain` isn't a valid domain, an `Error::Url` is returned.
+    pub fn block_domain(&self, domain: String) -> Result<Empty> {
+        let response = self.send(
+            self.client
+                .post(&self.route("/api/v1/domain_blocks"))
+                .headers(self.headers.clone())
+                .form(&[("domain", ascii_domain(&domain)?)]),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Stop hiding everything from `domain`. Internationalised domains are
+    /// sent as punycode.
+    ///
+    /// # Errors
+    /// If `domain` isn't a valid domain, an `Error::Url` is returned.
+    pub fn unblock_domain(&self, domain: String) -> Result<Empty> {
+        let response = self.send(
+            self.client
+                .delete(&self.route("/api/v1/domain_blocks"))
+                .headers(self.headers.clone())
+                .form(&[("domain", ascii_domain(&domain)?)]),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Get the announcements of the instance, including the ones the user
+    /// dismissed if `with_dismissed` is set.
+    pub fn announcements(&self, with_dismissed: bool) -> Result<Vec<Announcement>> {
+        let url = Url::parse_with_params(
+            &self.route("/api/v1/announcements"),
+            &[("with_dismissed", with_dismissed.to_string())],
+        )?;
+
+        self.get(url.into_string())
+    }
+
+    /// Mark an announcement as read.
+    pub fn dismiss_announcement(&self, id: &str) -> Result<Empty> {
+        self.post(self.route(&format!("/api/v1/announcements/{}/dismiss", id)))
+    }
+
+    /// React to an announcement with `name`, either a unicode emoji or the
+    /// shortcode of a custom emoji.
+    pub fn add_announcement_reaction(&self, id: &str, name: &str) -> Result<Empty> {
+        let response = self.send(
+            self.client
+                .put(&self.announcement_reaction_url(id, name))
+                .headers(self.headers.clone()),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Remove the user's reaction with `name` from an announcement.
+    pub fn remove_announcement_reaction(&self, id: &str, name: &str) -> Result<Empty> {
+        self.delete(self.announcement_reaction_url(id, name))
+    }
+
+    /// Feature a hashtag on the user's profile.
+    ///
+    /// # Errors
+    /// If the user has never used the hashtag, the server refuses with an
+    /// `Error::Api` explaining why.
+    pub fn feature_tag(&self, name: &str) -> Result<FeaturedTag> {
+        let response = self.send(
+            self.client
+                .post(&self.route("/api/v1/featured_tags"))
+                .headers(self.headers.clone())
+                .form(&[("name", name.trim_start_matches('#'))]),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Get the hashtags featured on an account's profile. Like
+    /// `custom_emojis` this works without authenticating.
+    pub fn account_featured_tags(&self, id: &str) -> Result<Vec<FeaturedTag>> {
+        let url = self.route(&format!("/api/v1/accounts/{}/featured_tags", id));
+
+        deserialise(self.get_public(&url)?)
     }
 
-    paged_routes! {
-        (get) favourites: "favourites" => Status,
-        (get) blocks: "blocks" => Account,
-        (get) domain_blocks: "domain_blocks" => String,
-        (get) follow_requests: "follow_requests" => Account,
-        (get) get_home_timeline: "timelines/home" => Status,
-        (get) get_emojis: "custom_emojis" => Emoji,
-        (get) mutes: "mutes" => Account,
-        (get) notifications: "notifications" => Notification,
-        (get) reports: "reports" => Report,
+    /// Get a hashtag by its name, with or without the leading `#`.
+    pub fn get_tag(&self, name: &str) -> Result<Tag> {
+        self.get(self.tag_url("/api/v1/tags/", name, ""))
     }
 
-    paged_routes_with_id! {
-        (get) followers: "accounts/{}/followers" => Account,
-        (get) following: "accounts/{}/following" => Account,
-        (get) reblogged_by: "statuses/{}/reblogged_by" => Account,
-        (get) favourited_by: "statuses/{}/favourited_by" => Account,
+    /// Follow a hashtag, so statuses using it show in the home timeline.
+    pub fn follow_tag(&self, name: &str) -> Result<Tag> {
+        self.post(self.tag_url("/api/v1/tags/", name, "/follow"))
     }
 
-    route! {
-        (delete (domain: String,)) unblock_domain: "domain_blocks" => Empty,
-        (get) instance: "instance" => Instance,
-        (get) verify_credentials: "accounts/verify_credentials" => Account,
-        (post (account_id: &str, status_ids: Vec<&str>, comment: String,)) report: "reports" => Report,
-        (post (domain: String,)) block_domain: "domain_blocks" => Empty,
-        (post (id: &str,)) authorize_follow_request: "accounts/follow_requests/authorize" => Empty,
-        (post (id: &str,)) reject_follow_request: "accounts/follow_requests/reject" => Empty,
-        (post (q: String, resolve: bool,)) search: "search" => SearchResult,
-        (post (uri: Cow<'static, str>,)) follows: "follows" => Account,
-        (post) clear_notifications: "notifications/clear" => Empty,
+    /// Stop following a hashtag.
+    pub fn unfollow_tag(&self, name: &str) -> Result<Tag> {
+        self.post(self.tag_url("/api/v1/tags/", name, "/unfollow"))
     }
 
-    route_id! {
-        (get) get_account: "accounts/{}" => Account,
-        (post) follow: "accounts/{}/follow" => Account,
-        (post) unfollow: "accounts/{}/unfollow" => Account,
-        (get) block: "accounts/{}/block" => Account,
-        (get) unblock: "accounts/{}/unblock" => Account,
-        (get) mute: "accounts/{}/mute" => Account,
-        (get) unmute: "accounts/{}/unmute" => Account,
-        (get) get_notification: "notifications/{}" => Notification,
-        (get) get_status: "statuses/{}" => Status,
-        (get) get_context: "statuses/{}/context" => Context,
-        (get) get_card: "statuses/{}/card" => Card,
-        (post) reblog: "statuses/{}/reblog" => Status,
-        (post) unreblog: "statuses/{}/unreblog" => Status,
-        (post) favourite: "statuses/{}/favourite" => Status,
-        (post) unfavourite: "statuses/{}/unfavourite" => Status,
-        (delete) delete_status: "statuses/{}" => Empty,
+    // The url of a route under `path` for the hashtag `name`, which may
+    // start with a `#`.
+    fn tag_url(&self, path: &str, name: &str, action: &str) -> String {
+        let name = utf8_percent_encode(name.trim_start_matches('#'), PATH_SEGMENT_ENCODE_SET);
+
+        self.route(&format!("{}{}{}", path, name, action))
     }
 
-    pub fn update_credentials(&self, changes: CredientialsBuilder) -> Result<Account> {
-        let url = self.route("/api/v1/accounts/update_credentials");
-        let response = self
-            .client
-            .patch(&url)
-            .headers(self.headers.clone())
-            .multipart(changes.into_form()?)
-            .send()?;
+    fn announcement_reaction_url(&self, id: &str, name: &str) -> String {
+        let name = utf8_percent_encode(name, PATH_SEGMENT_ENCODE_SET);
 
-        let status = response.status().clone();
+        self.route(&format!("/api/v1/announcements/{}/reactions/{}", id, name))
+    }
 
-        if status.is_client_error() {
-            return Err(Error::Client(status));
-        } else if status.is_server_error() {
-            return Err(Error::Server(status));
+    /// Get the hashtags trending on the instance, most trending first.
+    /// Instances older than 3.5 are asked with `/api/v1/trends` instead.
+    ///
+    /// Like `custom_emojis` this works without authenticating, but instances
+    /// may require it, which is an `Error::Api` or `Error::Client` with a
+    /// `401`. Instances that have trends turned off answer with a `404`.
+    pub fn trending_tags(&self, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<Tag>> {
+        let response = self.get_public(&trends_url(
+            self.route("/api/v1/trends/tags"),
+            limit,
+            offset,
+        )?)?;
+
+        if response.status() != StatusCode::NOT_FOUND {
+            return deserialise(response);
         }
 
-        deserialise(response)
+        deserialise(self.get_public(&trends_url(self.route("/api/v1/trends"), limit, offset)?)?)
     }
 
-    /// Post a new status to the account.
-    pub fn new_status(&self, status: StatusBuilder) -> Result<Status> {
-        let response = self
-            .client
-            .post(&self.route("/api/v1/statuses"))
-            .headers(self.headers.clone())
-            .json(&status)
-            .send()?;
+    /// Get the statuses trending on the instance, most trending first. See
+    /// `trending_tags` for when this fails.
+    pub fn trending_statuses(
+        &self,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<Status>> {
+        let url = trends_url(self.route("/api/v1/trends/statuses"), limit, offset)?;
 
-        deserialise(response)
+        deserialise(self.get_public(&url)?)
     }
 
-    /// Get the federated timeline for the instance.
-    pub fn get_public_timeline(&self, local: bool) -> Result<Vec<Status>> {
-        let mut url = self.route("/api/v1/timelines/public");
-
-        if local {
-            url += "?local=1";
-        }
+    /// Get the links trending on the instance, most trending first. See
+    /// `trending_tags` for when this fails.
+    pub fn trending_links(
+        &self,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<TrendsLink>> {
+        let url = trends_url(self.route("/api/v1/trends/links"), limit, offset)?;
 
-        self.get(url)
+        deserialise(self.get_public(&url)?)
     }
 
-    /// Get timeline filtered by a hashtag(eg. `#coffee`) either locally or
-    /// federated.
-    pub fn get_tagged_timeline(&self, hashtag: String, local: bool) -> Result<Vec<Status>> {
-        let mut url = self.route("/api/v1/timelines/tag/");
-        url += &hashtag;
-
-        if local {
-            url += "?local=1";
-        }
+    /// Search for accounts, statuses and hashtags with `/api/v1/search`.
+    /// Instances from 3.0 onwards only support `search_v2`.
+    #[deprecated(note = "use `search_v2`, `/api/v1/search` was removed in Mastodon 3.0")]
+    pub fn search(&self, q: String, resolve: bool) -> Result<SearchResult> {
+        let url = Url::parse_with_params(
+            &self.route("/api/v1/search"),
+            &[("q", q), ("resolve", resolve.to_string())],
+        )?;
 
-        self.get(url)
+        self.get(url.into_string())
     }
 
-    /// Get statuses of a single account by id. Optionally only with pictures
-    /// and or excluding replies.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # extern crate mammut;
-    /// # use mammut::{Data, Mastodon};
-    /// # use std::error::Error;
-    /// # fn main() -> Result<(), Box<Error>> {
-    /// # let data = Data {
-    /// #   base: "".into(),
-    /// #   client_id: "".into(),
-    /// #   client_secret: "".into(),
-    /// #   redirect: "".into(),
-    /// #   token: "".into(),
-    /// # };
-    /// let client = Mastodon::from_data(data);
-    /// let statuses = client.statuses("user-id", None)?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    ///
-    /// ```no_run
-    /// # extern crate mammut;
-    /// # use mammut::{Data, Mastodon, StatusesRequest};
-    /// # use std::error::Error;
-    /// # fn main() -> Result<(), Box<Error>> {
-    /// # let data = Data {
-    /// #   base: "".into(),
-    /// #   client_id: "".into(),
-    /// #   client_secret: "".into(),
-    /// #   redirect: "".into(),
-    /// #   token: "".into(),
-    /// # };
-    /// let client = Mastodon::from_data(data);
-    /// let request = StatusesRequest::default()
-    ///                               .only_media();
-    /// let statuses = client.statuses("user-id", request)?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn statuses<'a, S>(&self, id: &str, request: S) -> Result<Page<Status>>
+    /// Search for accounts, statuses and hashtags. Results of a single type
+    /// can be paged through with `SearchRequest::offset`.
+    pub fn search_v2<'a, R>(&self, q: &str, request: R) -> Result<SearchResultV2>
     where
-        S: Into<Option<StatusesRequest<'a>>>,
+        R: Into<Option<SearchRequest<'a>>>,
     {
-        let mut url = format!("{}/api/v1/accounts/{}/statuses", self.base, id);
+        let mut url = Url::parse(&self.route("/api/v2/search"))?;
+        url.query_pairs_mut().append_pair("q", q);
 
         if let Some(request) = request.into() {
-            url = format!("{}{}", url, request.to_querystring());
-        }
-
-        let response = self.client.get(&url).headers(self.headers.clone()).send()?;
-
-        Page::new(self, response)
-    }
-
-    /// Returns the client account's relationship to a list of other accounts.
-    /// Such as whether they follow them or vice versa.
-    pub fn relationships(&self, ids: &[&str]) -> Result<Page<Relationship>> {
-        let mut url = self.route("/api/v1/accounts/relationships?");
-
-        if ids.len() == 1 {
-            url += "id=";
-            url += &ids[0];
-        } else {
-            for id in ids {
-                url += "id[]=";
-                url += &id;
-                url += "&";
-            }
-            url.pop();
+            url.query_pairs_mut().extend_pairs(request.pairs());
         }
 
-        let response = self.client.get(&url).headers(self.headers.clone()).send()?;
-
-        Page::new(self, response)
+        self.get(url.into_string())
     }
 
     /// Search for accounts by their name.
@@ -684,22 +2991,152 @@ impl Mastodon {
         query: &str,
         limit: Option<u64>,
         following: bool,
-    ) -> Result<Page<Account>> {
+    ) -> Result<Page<'_, Account>> {
+        let url = Url::parse_with_params(
+            &self.route("/api/v1/accounts/search"),
+            &[
+                ("q", query),
+                ("limit", &limit.unwrap_or(40).to_string()),
+                ("following", &following.to_string()),
+            ],
+        )?;
+
+        let response = self.send(self.client.get(url).headers(self.headers.clone()))?;
+
+        Page::new(self, response)
+    }
+
+    /// Get the accounts in a list. A `limit` of `0` returns every account in
+    /// the list without paging, otherwise it defaults to 40.
+    pub fn list_accounts(&self, id: &str, limit: Option<u64>) -> Result<Page<'_, Account>> {
         let url = format!(
-            "{}/api/v1/accounts/search?q={}&limit={}&following={}",
+            "{}/api/v1/lists/{}/accounts?limit={}",
             self.base,
-            query,
-            limit.unwrap_or(40),
-            following
+            id,
+            limit.unwrap_or(40)
         );
 
-        let response = self.client.get(&url).headers(self.headers.clone()).send()?;
+        let response = self.send(self.client.get(&url).headers(self.headers.clone()))?;
 
         Page::new(self, response)
     }
 
+    /// Download the file at `url`, such as `Attachment::url` or
+    /// `Account::avatar`, streaming it into `writer`. Returns the number of
+    /// bytes written.
+    ///
+    /// The access token is only sent if `url` is on the instance itself, so
+    /// that it isn't leaked to remote servers or media hosts.
+    pub fn download<W: Write + ?Sized>(&self, url: &str, writer: &mut W) -> Result<u64> {
+        let url = Url::parse(url)?;
+        let headers = if url.origin() == Url::parse(&self.base)?.origin() {
+            self.headers.clone()
+        } else {
+            self.headers_without_token()
+        };
+
+        let mut response = self.client.get(url).headers(headers).send()?;
+        let status = response.status();
+
+        if status.is_client_error() {
+            return Err(Error::Client(status));
+        } else if status.is_server_error() {
+            return Err(Error::Server(status));
+        }
+
+        Ok(response.copy_to(writer)?)
+    }
+
+    /// Download the file at `url` to `path`, overwriting it if it already
+    /// exists. See `download`.
+    pub fn download_to_path<P: AsRef<Path>>(&self, url: &str, path: P) -> Result<u64> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let written = self.download(url, &mut writer)?;
+        writer.flush()?;
+
+        Ok(written)
+    }
+
     methods![get, post, delete,];
 
+    /// Send a GET request to `path` on the instance, such as
+    /// `/api/v1/trends`, and deserialise the response into `T`. Useful for
+    /// routes this crate doesn't cover yet.
+    pub fn get_raw<T: for<'de> serde::Deserialize<'de>>(&self, path: &str) -> Result<T> {
+        self.get(self.route(path))
+    }
+
+    /// Send a POST request to `path` on the instance with `body` as JSON, and
+    /// deserialise the response into `T`.
+    pub fn post_raw<T, B>(&self, path: &str, body: &B) -> Result<T>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+        B: serde::Serialize + ?Sized,
+    {
+        let response = self.send(
+            self.client
+                .post(&self.route(path))
+                .headers(self.headers.clone())
+                .json(body),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Send a DELETE request to `path` on the instance, and deserialise the
+    /// response into `T`.
+    pub fn delete_raw<T: for<'de> serde::Deserialize<'de>>(&self, path: &str) -> Result<T> {
+        self.delete(self.route(path))
+    }
+
+    /// The instance's rate limit for the user, as of the last response that
+    /// had one, or `None` before then. Shared by clones of the client.
+    ///
+    /// Only requests made through `Mastodon` are counted; the ones
+    /// `Registration` and `Registered` make to register the app and fetch
+    /// the token aren't.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        *self.rate_limit.read().unwrap()
+    }
+
+    // Send a request to the instance, keeping the rate limit from the
+    // response, and turning a `429 Too Many Requests` into
+    // `Error::RateLimited` when the instance says when to retry.
+    fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let response = request.send()?;
+        let rate_limit = RateLimit::from_headers(response.headers());
+
+        if let Some(rate_limit) = rate_limit {
+            *self.rate_limit.write().unwrap() = Some(rate_limit);
+        }
+
+        match rate_limit {
+            Some(RateLimit { reset, .. }) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                Err(Error::RateLimited { reset })
+            }
+            _ => Ok(response),
+        }
+    }
+
+    // Send a GET request, with the access token only if there is one.
+    fn get_public(&self, url: &str) -> Result<Response> {
+        let headers = if self.token.is_empty() {
+            self.headers_without_token()
+        } else {
+            self.headers.clone()
+        };
+
+        self.send(self.client.get(url).headers(headers))
+    }
+
+    // The headers sent with every request, such as the user agent, without
+    // the access token.
+    fn headers_without_token(&self) -> HeaderMap {
+        let mut headers = self.headers.clone();
+        headers.remove(header::AUTHORIZATION);
+        headers
+    }
+
     fn route(&self, url: &str) -> String {
         let mut s = (*self.base).to_owned();
         s += url;
@@ -708,38 +3145,138 @@ impl Mastodon {
 
     /// Equivalent to /api/v1/media
     pub fn media(&self, media_builder: MediaBuilder) -> Result<Attachment> {
-        use reqwest::multipart::Form;
+        self.upload_media("/api/v1/media", media_form(media_builder)?)
+    }
 
-        let mut form_data = Form::new().file("file", media_builder.file.as_ref())?;
+    /// Upload a media attachment with `/api/v2/media`, which processes large
+    /// files such as videos in the background. Until processing finishes the
+    /// returned attachment has no `url`, use `wait_for_media` to wait for it.
+    pub fn media_v2(&self, media_builder: MediaBuilder) -> Result<Attachment> {
+        self.upload_media("/api/v2/media", media_form(media_builder)?)
+    }
 
-        if let Some(description) = media_builder.description {
-            form_data = form_data.text("description", description);
-        }
+    /// Poll an attachment uploaded with `media_v2` until the server has
+    /// finished processing it, waiting longer between each request.
+    ///
+    /// # Errors
+    /// If the attachment is still processing after `timeout`,
+    /// `Error::ProcessingTimeout` is returned. If processing failed the
+    /// server's error is returned instead.
+    pub fn wait_for_media(&self, id: &str, timeout: Duration) -> Result<Attachment> {
+        let url = self.route(&format!("/api/v1/media/{}", id));
+        let start = Instant::now();
+        let mut delay = Duration::from_millis(500);
+
+        loop {
+            let response = self.send(self.client.get(&url).headers(self.headers.clone()))?;
+
+            // 206 Partial Content means the attachment is still processing.
+            if response.status() != StatusCode::PARTIAL_CONTENT {
+                return deserialise(response);
+            }
+
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Err(Error::ProcessingTimeout);
+            }
 
-        if let Some(focus) = media_builder.focus {
-            let string = format!("{},{}", focus.0, focus.1);
-            form_data = form_data.text("focus", string);
+            thread::sleep(delay.min(timeout - elapsed));
+            delay = (delay * 2).min(Duration::from_secs(5));
         }
+    }
 
-        let response = self
-            .client
-            .post(&self.route("/api/v1/media"))
-            .headers(self.headers.clone())
-            .multipart(form_data)
-            .send()?;
+    /// Change the alt text, focal point or thumbnail of an attachment that
+    /// hasn't been posted in a status yet. Fields that are `None` are left
+    /// unchanged.
+    ///
+    /// # Errors
+    /// If a coordinate of `focus` isn't within `-1.0` to `1.0`, an
+    /// `Error::InvalidFocus` is returned without contacting the server.
+    pub fn update_media(
+        &self,
+        id: &str,
+        description: Option<&str>,
+        focus: Option<(f32, f32)>,
+        thumbnail: Option<Thumbnail>,
+    ) -> Result<Attachment> {
+        let mut form_data = reqwest::multipart::Form::new();
+
+        if let Some(description) = description {
+            form_data = form_data.text("description", description.to_owned());
+        }
 
-        let status = response.status().clone();
+        if let Some(focus) = focus {
+            form_data = form_data.text("focus", focus_param(focus)?);
+        }
 
-        if status.is_client_error() {
-            return Err(Error::Client(status));
-        } else if status.is_server_error() {
-            return Err(Error::Server(status));
+        if let Some(thumbnail) = thumbnail {
+            form_data = form_data.part("thumbnail", thumbnail.into_part()?);
         }
 
+        let response = self.send(
+            self.client
+                .put(&self.route(&format!("/api/v1/media/{}", id)))
+                .headers(self.headers.clone())
+                .multipart(form_data),
+        )?;
+
+        deserialise(response)
+    }
+
+    /// Upload the file at `path` as a media attachment. The file name and
+    /// mime type are taken from the path's extension.
+    pub fn media_from_path(&self, path: &Path) -> Result<Attachment> {
+        use reqwest::multipart::Form;
+
+        self.upload_media("/api/v1/media", Form::new().file("file", path)?)
+    }
+
+    /// Upload `bytes` as a media attachment with the given file name and mime
+    /// type, such as `"image/png"`.
+    pub fn media_from_bytes(
+        &self,
+        bytes: Vec<u8>,
+        filename: &str,
+        mime: &str,
+    ) -> Result<Attachment> {
+        use reqwest::multipart::{Form, Part};
+
+        let part = Part::bytes(bytes)
+            .file_name(filename.to_owned())
+            .mime_str(mime)?;
+
+        self.upload_media("/api/v1/media", Form::new().part("file", part))
+    }
+
+    fn upload_media(&self, url: &str, form_data: reqwest::multipart::Form) -> Result<Attachment> {
+        let response = self.send(
+            self.client
+                .post(&self.route(url))
+                .headers(self.headers.clone())
+                .multipart(form_data),
+        )?;
+
         deserialise(response)
     }
 }
 
+/// Pleroma and Akkoma's extensions to the Mastodon API. Only available with
+/// the `pleroma` feature.
+#[cfg(feature = "pleroma")]
+impl Mastodon {
+    route! {
+        (get) chats: "pleroma/chats" => Vec<Chat>,
+    }
+
+    paged_routes_with_id! {
+        (get) chat_messages: "pleroma/chats/{}/messages" => [ChatMessage; MAX_STATUSES_LIMIT],
+    }
+
+    route_id! {
+        (post (content: &str,)) post_chat_message: "pleroma/chats/{}/messages" => ChatMessage,
+    }
+}
+
 impl ops::Deref for Mastodon {
     type Target = Data;
 
@@ -768,6 +3305,153 @@ from! {
     UrlError, Url,
 }
 
+/// The largest `limit` of the routes returning statuses, such as the
+/// timelines and `favourites`, and of searches, which allow up to 40 items
+/// per page.
+pub const MAX_STATUSES_LIMIT: u32 = 40;
+
+/// The largest `limit` of the routes returning accounts, such as `followers`
+/// and `blocks`, and of the other collection routes, which allow up to 80
+/// items per page.
+pub const MAX_ACCOUNTS_LIMIT: u32 = 80;
+
+// Build the multipart form to upload a media attachment.
+fn media_form(media_builder: MediaBuilder) -> Result<reqwest::multipart::Form> {
+    let mut form_data =
+        reqwest::multipart::Form::new().file("file", media_builder.file.as_ref())?;
+
+    if let Some(description) = media_builder.description {
+        form_data = form_data.text("description", description);
+    }
+
+    if let Some(focus) = media_builder.focus {
+        form_data = form_data.text("focus", focus_param(focus)?);
+    }
+
+    if let Some(thumbnail) = media_builder.thumbnail {
+        form_data = form_data.part("thumbnail", thumbnail.into_part()?);
+    }
+
+    Ok(form_data)
+}
+
+// Format a media focal point as `x,y`, checking both coordinates are in range.
+fn focus_param((x, y): (f32, f32)) -> Result<String> {
+    let range = -1.0..=1.0;
+    if !range.contains(&x) || !range.contains(&y) {
+        return Err(Error::InvalidFocus(x, y));
+    }
+
+    Ok(format!("{},{}", x, y))
+}
+
+// The `Authorization` header sending `token`.
+fn auth_header(token: &str) -> Result<HeaderValue> {
+    Ok(HeaderValue::from_str(&format!("Bearer {}", token))?)
+}
+
+// Add a `limit`, clamped to `max`, to the url of a collection route.
+fn with_limit(url: String, limit: Option<u32>, max: u32) -> String {
+    match limit {
+        Some(limit) => format!("{}?limit={}", url, limit.min(max)),
+        None => url,
+    }
+}
+
+// Add the `local` flag and the options of a request to a timeline's url.
+fn timeline_url(url: String, local: bool, request: Option<StatusesRequest<'_>>) -> Result<String> {
+    let mut pairs = request.map(|request| request.pairs()).unwrap_or_default();
+
+    if local {
+        pairs.push(("local", "1".into()));
+    }
+
+    Ok(Url::parse_with_params(&url, pairs)?.into_string())
+}
+
+// Whether two handles are the same account. Local accounts are returned
+// without a domain, so `local_domain` is used for them. Usernames must match
+// exactly, but domains are case insensitive.
+fn acct_matches(wanted: &str, candidate: &str, local_domain: Option<&str>) -> bool {
+    let split = |acct: &str| match acct.split_once('@') {
+        Some((username, domain)) => (username.to_owned(), domain.to_owned()),
+        None => (acct.to_owned(), local_domain.unwrap_or_default().to_owned()),
+    };
+
+    let (username, domain) = split(wanted);
+    let (candidate_username, candidate_domain) = split(candidate);
+
+    username == candidate_username && domain.eq_ignore_ascii_case(&candidate_domain)
+}
+
+// Convert a domain to the ASCII form the server expects, such as
+// `xn--bcher-kva.example` for `bücher.example`.
+fn ascii_domain(domain: &str) -> Result<String> {
+    Ok(url::Host::parse(domain.trim())?.to_string())
+}
+
+// Add the `limit` and `offset` of a request to one of the trends' url.
+fn trends_url(url: String, limit: Option<u32>, offset: Option<u32>) -> Result<String> {
+    let params = limit
+        .map(|limit| ("limit", limit))
+        .into_iter()
+        .chain(offset.map(|offset| ("offset", offset)))
+        .map(|(key, val)| (key, val.to_string()));
+
+    Ok(Url::parse_with_params(&url, params)?.into_string())
+}
+
+// Build the form for creating or updating a v2 filter. Keywords are sent as
+// `keywords_attributes[][keyword]` and so on, with `keyword` first so that
+// Rails starts a new keyword at each one rather than merging them.
+fn filter_v2_form(
+    title: &str,
+    context: &[FilterContext],
+    filter_action: Option<FilterAction>,
+    expires_in: Option<u64>,
+    keywords: &[FilterKeywordAttributes<'_>],
+) -> Vec<(String, String)> {
+    let mut form = vec![("title".to_owned(), title.to_owned())];
+
+    // `FilterContext::Unknown` and `FilterAction::Unknown` can't be
+    // serialised, and are left out.
+    let as_string = |value: json::Result<json::Value>| match value {
+        Ok(json::Value::String(value)) => Some(value),
+        _ => None,
+    };
+
+    for context in context
+        .iter()
+        .filter_map(|context| as_string(json::to_value(context)))
+    {
+        form.push(("context[]".to_owned(), context));
+    }
+
+    if let Some(action) = filter_action.and_then(|action| as_string(json::to_value(action))) {
+        form.push(("filter_action".to_owned(), action));
+    }
+
+    if let Some(expires_in) = expires_in {
+        form.push(("expires_in".to_owned(), expires_in.to_string()));
+    }
+
+    for keyword in keywords {
+        let key = |field: &str| format!("keywords_attributes[][{}]", field);
+        form.push((key("keyword"), keyword.keyword.to_owned()));
+        form.push((key("whole_word"), keyword.whole_word.to_string()));
+
+        if let Some(id) = keyword.id {
+            form.push((key("id"), id.to_owned()));
+        }
+
+        if keyword.destroy {
+            form.push((key("_destroy"), "true".to_owned()));
+        }
+    }
+
+    form
+}
+
 // Convert the HTTP response body from JSON. Pass up deserialization errors
 // transparently.
 fn deserialise<T: for<'de> serde::Deserialize<'de>>(mut response: Response) -> Result<T> {
@@ -776,15 +3460,72 @@ fn deserialise<T: for<'de> serde::Deserialize<'de>>(mut response: Response) -> R
     let mut vec = Vec::new();
     response.read_to_end(&mut vec)?;
 
-    match json::from_slice(&vec) {
+    let status = response.status();
+    let is_empty = vec.iter().all(u8::is_ascii_whitespace);
+    // A body that isn't JSON at all didn't come from the API, such as an
+    // error page from a proxy, so return it rather than a confusing parse
+    // error.
+    let unexpected = |vec: &[u8]| Error::UnexpectedResponse {
+        status,
+        content_type: response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from),
+        body: String::from_utf8_lossy(vec).into_owned(),
+    };
+
+    // Error responses can deserialise successfully into types such as `Empty`,
+    // so check the status before trying the desired type.
+    if status.is_client_error() || status.is_server_error() {
+        return Err(match json::from_slice(&vec) {
+            Ok(error) => Error::Api(ApiError {
+                status: Some(status),
+                ..error
+            }),
+            Err(_) if !is_empty && json::from_slice::<json::Value>(&vec).is_err() => {
+                unexpected(&vec)
+            }
+            Err(_) if status.is_client_error() => Error::Client(status),
+            Err(_) => Error::Server(status),
+        });
+    }
+
+    // Routes without a result may answer with no body at all, such as with
+    // `204 No Content`, which is read as an empty object.
+    let body: &[u8] = if is_empty { b"{}" } else { &vec };
+
+    match json::from_slice(body) {
         Ok(t) => Ok(t),
-        // If deserializing into the desired type fails try again to
-        // see if this is an error response.
-        Err(e) => {
-            if let Ok(error) = json::from_slice(&vec) {
-                return Err(Error::Api(error));
+        Err(source) => {
+            if json::from_slice::<json::Value>(body).is_err() {
+                return Err(unexpected(&vec));
+            }
+            // Some servers send errors with a successful status. Every field
+            // of `ApiError` is optional, so only treat the body as one if it
+            // has an `error`, otherwise any object would match.
+            if let Ok(error @ ApiError { error: Some(_), .. }) = json::from_slice(&vec) {
+                return Err(Error::Api(ApiError {
+                    status: Some(status),
+                    ..error
+                }));
             }
-            Err(e.into())
+            Err(Error::Deserialize {
+                source,
+                body_snippet: body_snippet(&vec),
+            })
         }
     }
 }
+
+// The start of a response's body, for errors about it, without cutting a
+// character in half.
+fn body_snippet(body: &[u8]) -> String {
+    const MAX_LEN: usize = 512;
+
+    let body = String::from_utf8_lossy(body);
+    match body.char_indices().nth(MAX_LEN) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.into_owned(),
+    }
+}
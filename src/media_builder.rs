@@ -1,4 +1,9 @@
 use std::borrow::Cow;
+use std::path::PathBuf;
+
+use reqwest::multipart::Part;
+
+use crate::Result;
 
 /// A builder pattern struct for constructing a media attachment.
 #[derive(Debug, Default, Clone, Serialize)]
@@ -9,6 +14,40 @@ pub struct MediaBuilder {
     pub description: Option<Cow<'static, str>>,
     /// The focus point for images.
     pub focus: Option<(f32, f32)>,
+    /// A preview image for audio or video.
+    #[serde(skip)]
+    pub thumbnail: Option<Thumbnail>,
+}
+
+/// A preview image for an audio or video attachment, such as cover art.
+#[derive(Debug, Clone)]
+pub enum Thumbnail {
+    /// Upload the image at a path. The file name and mime type are taken from
+    /// the path's extension.
+    Path(PathBuf),
+    /// Upload an image from memory.
+    Bytes {
+        /// The contents of the image.
+        bytes: Vec<u8>,
+        /// The file name of the image.
+        filename: String,
+        /// The mime type of the image, such as `"image/png"`.
+        mime: String,
+    },
+}
+
+impl Thumbnail {
+    /// Turn the thumbnail into a part of a multipart form.
+    pub(crate) fn into_part(self) -> Result<Part> {
+        Ok(match self {
+            Thumbnail::Path(path) => Part::file(path)?,
+            Thumbnail::Bytes {
+                bytes,
+                filename,
+                mime,
+            } => Part::bytes(bytes).file_name(filename).mime_str(&mime)?,
+        })
+    }
 }
 
 impl MediaBuilder {
@@ -18,6 +57,7 @@ impl MediaBuilder {
             file,
             description: None,
             focus: None,
+            thumbnail: None,
         }
     }
     /// Set an alt text description for the attachment.
@@ -26,11 +66,18 @@ impl MediaBuilder {
         self
     }
 
-    /// Set a focus point for an image attachment.
+    /// Set a focus point for an image attachment. Both coordinates must be
+    /// from `-1.0` to `1.0`, or uploading returns `Error::InvalidFocus`.
     pub fn focus(mut self, f1: f32, f2: f32) -> Self {
         self.focus = Some((f1, f2));
         self
     }
+
+    /// Set a preview image for an audio or video attachment.
+    pub fn thumbnail(mut self, thumbnail: Thumbnail) -> Self {
+        self.thumbnail = Some(thumbnail);
+        self
+    }
 }
 
 // Convenience helper so that the mastodon.media() method can be called with a
@@ -41,6 +88,7 @@ impl From<String> for MediaBuilder {
             file: file.into(),
             description: None,
             focus: None,
+            thumbnail: None,
         }
     }
 }
@@ -53,6 +101,7 @@ impl From<&'static str> for MediaBuilder {
             file: file.into(),
             description: None,
             focus: None,
+            thumbnail: None,
         }
     }
 }
@@ -65,6 +114,7 @@ impl From<Cow<'static, str>> for MediaBuilder {
             file,
             description: None,
             focus: None,
+            thumbnail: None,
         }
     }
 }
@@ -7,6 +7,7 @@ use url::Url;
 use super::{deserialise, Mastodon, Result};
 use crate::entities::itemsiter::ItemsIter;
 
+/// Represents a single page of API results
 pub struct Page<'a, T: for<'de> Deserialize<'de>> {
     mastodon: &'a Mastodon,
     next: Option<Url>,
@@ -19,27 +20,30 @@ macro_rules! pages {
     ($($direction:ident: $fun:ident),*) => {
 
         $(
+            doc_comment!(concat!(
+                "Method to retrieve the ", stringify!($direction), " page of results"),
             pub fn $fun(&mut self) -> Result<Option<Vec<T>>> {
                 let url = match self.$direction.take() {
                     Some(s) => s,
                     None => return Ok(None),
                 };
 
-                let response = self.mastodon.client.get(url)
-                    .headers(self.mastodon.headers.clone())
-                    .send()?;
+                let request = self.mastodon.client.get(url)
+                    .headers(self.mastodon.headers.clone());
+                let response = self.mastodon.send(request)?;
 
                 let (prev, next) = get_links(&response)?;
                 self.next = next;
                 self.prev = prev;
 
                 deserialise(response)
-            }
+            });
          )*
     }
 }
 
 impl<'a, T: for<'de> Deserialize<'de>> Page<'a, T> {
+    /// Create a new Page.
     pub fn new(mastodon: &'a Mastodon, response: Response) -> Result<Self> {
         let (prev, next) = get_links(&response)?;
         Ok(Page {
@@ -77,6 +81,7 @@ impl<'a, T: Clone + for<'de> Deserialize<'de>> Page<'a, T> {
     /// #       client_secret: "".into(),
     /// #       redirect: "".into(),
     /// #       token: "".into(),
+    /// #       token_kind: Default::default(),
     /// #   };
     /// let mastodon = Mastodon::from_data(data);
     /// let req = StatusesRequest::new();
@@ -0,0 +1,62 @@
+use chrono::prelude::*;
+use reqwest::header::HeaderMap;
+
+/// The instance's rate limit for the user, from the `X-RateLimit-*` headers
+/// of a response.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate reqwest;
+/// use mammut::RateLimit;
+/// use reqwest::header::{HeaderMap, HeaderValue};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("X-RateLimit-Limit", HeaderValue::from_static("300"));
+/// headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("299"));
+/// // An ISO 8601 timestamp, not seconds since the epoch.
+/// headers.insert(
+///     "X-RateLimit-Reset",
+///     HeaderValue::from_static("2017-06-26T00:00:00.000Z"),
+/// );
+///
+/// let rate_limit = RateLimit::from_headers(&headers).unwrap();
+/// assert_eq!(rate_limit.limit, 300);
+/// assert_eq!(rate_limit.remaining, 299);
+/// assert_eq!(rate_limit.reset.to_rfc3339(), "2017-06-26T00:00:00+00:00");
+///
+/// headers.remove("X-RateLimit-Reset");
+/// assert!(RateLimit::from_headers(&headers).is_none());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The number of requests allowed in the current period.
+    pub limit: u64,
+    /// The number of requests left in the current period.
+    pub remaining: u64,
+    /// When the period ends and `remaining` goes back to `limit`.
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// Read the rate limit from a response's headers, if they have all of
+    /// `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset`.
+    ///
+    /// Mastodon sends the reset time as an ISO 8601 timestamp. Seconds since
+    /// the epoch, as some other servers send, are also accepted.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| headers.get(name)?.to_str().ok().map(str::trim);
+
+        Some(RateLimit {
+            limit: header("X-RateLimit-Limit")?.parse().ok()?,
+            remaining: header("X-RateLimit-Remaining")?.parse().ok()?,
+            reset: parse_reset(header("X-RateLimit-Reset")?)?,
+        })
+    }
+}
+
+fn parse_reset(reset: &str) -> Option<DateTime<Utc>> {
+    match DateTime::parse_from_rfc3339(reset) {
+        Ok(reset) => Some(reset.with_timezone(&Utc)),
+        Err(_) => Utc.timestamp_opt(reset.parse().ok()?, 0).single(),
+    }
+}
@@ -1,17 +1,27 @@
+use rand::RngCore;
+use reqwest::header::{self, HeaderMap, HeaderValue};
 use reqwest::Client;
+use sha2::{Digest, Sha256};
+use url::{form_urlencoded, Url};
 
-use super::{Error, Mastodon, Result};
-use crate::apps::{AppBuilder, Scopes};
+use super::{deserialise, ApiError, Error, Mastodon, Result, TokenKind};
+use crate::apps::{App, AppBuilder, Redirect, Scopes};
 
-/// Handles registering your mastodon app to your instance. It is recommended
-/// you cache your data struct to avoid registering on every run.
+/// Handles registering your mastodon app to your instance.
+///
+/// Set the details of the app with the builder methods, then `build` it to
+/// register it with the instance and get a `Registered` app to authorise.
 pub struct Registration {
     base: String,
     client: Client,
+    app: AppBuilder<'static>,
     client_id: Option<String>,
     client_secret: Option<String>,
+    redirect_uris: Vec<String>,
     redirect: Option<String>,
-    scopes: Scopes,
+    pkce: bool,
+    code_verifier: Option<String>,
+    user_agent: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -28,6 +38,11 @@ struct AccessToken {
 
 impl Registration {
     /// Construct a new registration process to the instance of the `base` url.
+    ///
+    /// The app is named `mammut`, only has the `read` scope and uses
+    /// `urn:ietf:wg:oauth:2.0:oob` to show the code to the user instead of
+    /// redirecting, unless they're changed with the builder methods.
+    ///
     /// ```
     /// use mammut::registration::Registration;
     ///
@@ -37,100 +52,743 @@ impl Registration {
         Registration {
             base: base.into(),
             client: Client::new(),
+            app: App::builder().client_name("mammut"),
             client_id: None,
             client_secret: None,
+            redirect_uris: Vec::new(),
             redirect: None,
-            scopes: Scopes::Read,
+            pkce: true,
+            code_verifier: None,
+            user_agent: None,
         }
     }
 
-    /// Register the application with the server from the `base` url.
+    /// Construct a registration process for an app that has already been
+    /// registered with the instance. `build` then skips registering it again.
+    ///
+    /// The scopes must be the same ones the app was registered with.
+    ///
+    /// ```
+    /// # extern crate mammut;
+    /// # extern crate serde_json;
+    /// # extern crate url;
+    /// use mammut::apps::{App, Scopes};
+    /// use mammut::registration::Registration;
+    ///
+    /// let scopes = Scopes::READ_STATUSES | Scopes::WRITE_MEDIA | Scopes::PUSH;
+    /// let registered = Registration::from_app(
+    ///     "https://mastodon.social",
+    ///     "CLIENT_ID",
+    ///     "CLIENT_SECRET",
+    ///     "urn:ietf:wg:oauth:2.0:oob",
+    ///     scopes,
+    /// )
+    /// .build()
+    /// .unwrap();
+    /// let url = registered.authorize_url();
+    /// assert!(url.starts_with("https://mastodon.social/oauth/authorize?client_id=CLIENT_ID&"));
+    ///
+    /// // The scopes are sent the same way when registering and authorising.
+    /// let app = App::builder().client_name("mammut_test").scopes(scopes).build().unwrap();
+    /// let registered = serde_json::to_value(&app).unwrap()["scopes"].clone();
+    /// let url = url::Url::parse(&url).unwrap();
+    /// let (_, authorised) = url.query_pairs().find(|(key, _)| key == "scope").unwrap();
+    /// assert_eq!(registered, *authorised);
+    /// assert_eq!(authorised.parse::<Scopes>().unwrap(), scopes);
+    /// ```
+    pub fn from_app<I: Into<String>>(
+        base: I,
+        client_id: I,
+        client_secret: I,
+        redirect: I,
+        scopes: Scopes,
+    ) -> Self {
+        let redirect = redirect.into();
+
+        Registration {
+            client_id: Some(client_id.into()),
+            client_secret: Some(client_secret.into()),
+            redirect_uris: vec![redirect.clone()],
+            redirect: Some(redirect),
+            ..Registration::new(base).scopes(scopes)
+        }
+    }
+
+    /// Set the name of the application, displayed when the user is deciding
+    /// to grant permission.
+    pub fn client_name<I: Into<String>>(mut self, client_name: I) -> Self {
+        self.app = self.app.client_name(client_name.into());
+        self
+    }
+
+    /// Set where the user should be redirected after authorising.
+    pub fn redirect(mut self, redirect: Redirect) -> Self {
+        self.app = self.app.redirect(&redirect);
+        self
+    }
+
+    /// Set the permissions the application asks for.
+    pub fn scopes(mut self, scopes: Scopes) -> Self {
+        self.app = self.app.scopes(scopes);
+        self
+    }
+
+    /// Set whether to use [PKCE](https://tools.ietf.org/html/rfc7636) when
+    /// authorising, which is on by default. Only turn it off for servers
+    /// that refuse the extra parameters.
+    ///
+    /// ```
+    /// use mammut::registration::Registration;
+    ///
+    /// let registration = || {
+    ///     Registration::from_app(
+    ///         "https://mastodon.social",
+    ///         "CLIENT_ID",
+    ///         "CLIENT_SECRET",
+    ///         "urn:ietf:wg:oauth:2.0:oob",
+    ///         Default::default(),
+    ///     )
+    /// };
+    /// let url = registration().build().unwrap().authorize_url();
+    /// assert!(url.contains("code_challenge_method=S256"));
+    ///
+    /// let url = registration().pkce(false).build().unwrap().authorize_url();
+    /// assert!(!url.contains("code_challenge"));
+    /// ```
+    pub fn pkce(mut self, pkce: bool) -> Self {
+        self.pkce = pkce;
+        self
+    }
+
+    /// Set the `User-Agent` header sent when registering and authorising the
+    /// app, which the `Mastodon` client it creates also sends with every
+    /// request, like `MastodonBuilder::user_agent`.
+    ///
+    /// # Errors
+    /// If the user agent can't be sent as a header, such as when it contains
+    /// a newline, `build` returns an `Error::InvalidHeaderValue`.
+    ///
+    /// ```
+    /// use mammut::registration::Registration;
+    ///
+    /// let registration = |user_agent| {
+    ///     Registration::from_app(
+    ///         "https://mastodon.social",
+    ///         "CLIENT_ID",
+    ///         "CLIENT_SECRET",
+    ///         "urn:ietf:wg:oauth:2.0:oob",
+    ///         Default::default(),
+    ///     )
+    ///     .user_agent(user_agent)
+    /// };
+    /// assert!(registration("MyBot/1.0 (+https://example.com/bot)").build().is_ok());
+    /// assert!(registration("MyBot\n").build().is_err());
+    /// ```
+    pub fn user_agent<I: Into<String>>(mut self, user_agent: I) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Set the url to the homepage of the application.
+    pub fn website<I: Into<String>>(mut self, website: I) -> Self {
+        self.app = self.app.website(website.into());
+        self
+    }
+
+    /// Set all the details of the application at once.
+    pub fn app(mut self, app: App) -> Self {
+        self.app = app.into();
+        self
+    }
+
+    /// Register the application with the server from the `base` url, unless
+    /// it was already registered with `from_app`.
     ///
     /// ```no_run
     /// # extern crate mammut;
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use mammut::Registration;
-    /// use mammut::apps::{AppBuilder, Scopes};
+    /// use mammut::apps::Scopes;
     ///
-    /// let app = AppBuilder {
-    ///     client_name: "mammut_test",
-    ///     redirect_uris: "urn:ietf:wg:oauth:2.0:oob",
-    ///     scopes: Scopes::Read,
-    ///     website: None,
-    /// };
-    ///
-    /// let mut registration = Registration::new("https://mastodon.social");
-    /// registration.register(app)?;
-    /// let url = registration.authorise()?;
+    /// let registered = Registration::new("https://mastodon.social")
+    ///     .client_name("mammut_test")
+    ///     .scopes(Scopes::READ)
+    ///     .build()?;
+    /// let url = registered.authorize_url();
     /// // Here you now need to open the url in the browser
     /// // And handle a the redirect url coming back with the code.
-    /// let code = String::from("RETURNED_FROM_BROWSER");
-    /// let mastodon = registration.create_access_token(code)?;
+    /// let code = "RETURNED_FROM_BROWSER";
+    /// let mastodon = registered.complete(code)?;
     ///
-    /// println!("{:?}", mastodon.get_home_timeline()?.initial_items);
+    /// println!("{:?}", mastodon.get_home_timeline(None)?.initial_items);
     /// # Ok(())
     /// # }
     /// ```
+    pub fn build(mut self) -> Result<Registered> {
+        user_agent_headers(self.user_agent.as_deref())?;
+
+        if self.client_id.is_none() {
+            let app = self.app.clone().build()?;
+            let app = self.post_app(&app)?;
+            self.set_app(app);
+        }
+
+        self.to_registered(self.new_code_verifier())
+    }
+
+    /// Register the application with the server from the `base` url.
+    #[deprecated(note = "use `Registration::build`")]
     pub fn register(&mut self, app_builder: AppBuilder) -> Result<()> {
+        let app = app_builder.build()?;
+        self.app = self.app.clone().scopes(app.scopes());
+        let app = self.post_app(&app)?;
+        self.set_app(app);
+
+        Ok(())
+    }
+
+    fn post_app(&self, app: &App) -> Result<OAuth> {
         let url = format!("{}/api/v1/apps", self.base);
-        self.scopes = app_builder.scopes;
-        let app: OAuth = self.client.post(&url).form(&app_builder).send()?.json()?;
 
+        let headers = user_agent_headers(self.user_agent.as_deref())?;
+
+        deserialise(self.client.post(&url).headers(headers).form(app).send()?)
+    }
+
+    fn set_app(&mut self, app: OAuth) {
         self.client_id = Some(app.client_id);
         self.client_secret = Some(app.client_secret);
-        self.redirect = Some(app.redirect_uri);
-
-        Ok(())
+        // Multiple redirect uris are returned separated by newlines, the
+        // first is used unless another is picked with `with_redirect`.
+        self.redirect_uris = app.redirect_uri.lines().map(String::from).collect();
+        self.redirect = self.redirect_uris.first().cloned();
     }
 
     /// Returns the full url needed for authorisation. This needs to be opened
     /// in a browser.
+    ///
+    /// A new [PKCE](https://tools.ietf.org/html/rfc7636) code verifier is
+    /// generated on every call and kept until `create_access_token`, so the
+    /// code returned from the most recent url must be used.
+    #[deprecated(note = "use `Registration::build` and `Registered::authorize_url`")]
     pub fn authorise(&mut self) -> Result<String> {
-        self.is_registered()?;
+        let registered = self.to_registered(self.new_code_verifier())?;
+        self.code_verifier = registered.code_verifier.clone();
 
-        let url = format!(
-            "{}/oauth/authorize?client_id={}&redirect_uri={}&scope={}&response_type=code",
-            self.base,
-            self.client_id.clone().unwrap(),
-            self.redirect.clone().unwrap(),
-            self.scopes,
-        );
+        Ok(registered.authorize_url())
+    }
 
-        Ok(url)
+    fn new_code_verifier(&self) -> Option<String> {
+        if self.pkce {
+            Some(code_verifier())
+        } else {
+            None
+        }
     }
 
+    // Empty values, such as from `from_app` with placeholders left blank,
+    // count as missing.
     fn is_registered(&self) -> Result<()> {
-        if self.client_id.is_none() {
+        let missing = |value: &Option<String>| value.as_ref().map_or(true, |v| v.trim().is_empty());
+
+        if missing(&self.client_id) {
             Err(Error::ClientIdRequired)
-        } else if self.client_secret.is_none() {
+        } else if missing(&self.client_secret) {
             Err(Error::ClientSecretRequired)
         } else {
             Ok(())
         }
     }
 
+    fn to_registered(&self, code_verifier: Option<String>) -> Result<Registered> {
+        self.is_registered()?;
+
+        Ok(Registered {
+            base: self.base.clone(),
+            client_id: self.client_id.clone().unwrap(),
+            client_secret: self.client_secret.clone().unwrap(),
+            redirect_uris: self.redirect_uris.clone(),
+            redirect: self.redirect.clone().unwrap_or_default(),
+            scopes: self.app.scopes,
+            code_verifier,
+            user_agent: self.user_agent.clone(),
+            client: self.client.clone(),
+        })
+    }
+
     /// Create an access token from the client id, client secret, and code
     /// provided by the authorisation url.
+    #[deprecated(note = "use `Registration::build` and `Registered::complete`")]
     pub fn create_access_token(self, code: String) -> Result<Mastodon> {
-        self.is_registered()?;
-        let url = format!(
-            "{}/oauth/token?client_id={}&client_secret={}&code={}&grant_type=authorization_code&redirect_uri={}",
-            self.base,
-            self.client_id.clone().unwrap(),
-            self.client_secret.clone().unwrap(),
-            code,
-            self.redirect.clone().unwrap()
+        self.to_registered(self.code_verifier.clone())?
+            .complete(&code)
+    }
+
+    /// Create an access token for the app itself, without a user.
+    #[deprecated(note = "use `Registration::build` and `Registered::create_app_token`")]
+    pub fn create_app_token(self) -> Result<Mastodon> {
+        self.to_registered(None)?.create_app_token()
+    }
+}
+
+/// An app registered with an instance, ready to be authorised by a user.
+///
+/// It can be serialised to keep it while the user authorises the app in
+/// their browser, instead of registering the app again.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::registration::{Registered, Registration};
+///
+/// let registered = Registration::from_app(
+///     "https://mastodon.social",
+///     "CLIENT_ID",
+///     "CLIENT_SECRET",
+///     "urn:ietf:wg:oauth:2.0:oob",
+///     Default::default(),
+/// )
+/// .build()
+/// .unwrap();
+///
+/// let saved = serde_json::to_string(&registered).unwrap();
+/// let loaded: Registered = serde_json::from_str(&saved).unwrap();
+/// assert_eq!(loaded.authorize_url(), registered.authorize_url());
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Registered {
+    base: String,
+    client_id: String,
+    client_secret: String,
+    #[serde(default)]
+    redirect_uris: Vec<String>,
+    redirect: String,
+    scopes: Scopes,
+    #[serde(default)]
+    code_verifier: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    user_agent: Option<String>,
+    #[serde(skip, default = "Client::new")]
+    client: Client,
+}
+
+impl Registered {
+    /// Use `uri` as the redirect uri, when the app was registered with
+    /// several using `Redirect::Multiple`. The first is used otherwise.
+    ///
+    /// # Errors
+    /// If `uri` isn't exactly one the app was registered with,
+    /// `Error::RedirectMismatch` is returned, as the instance would refuse
+    /// it.
+    ///
+    /// ```
+    /// use mammut::registration::Registration;
+    ///
+    /// let registered = Registration::from_app(
+    ///     "https://mastodon.social",
+    ///     "CLIENT_ID",
+    ///     "CLIENT_SECRET",
+    ///     "https://example.com/callback",
+    ///     Default::default(),
+    /// )
+    /// .build()
+    /// .unwrap();
+    ///
+    /// let error = registered.clone().with_redirect("https://example.com/callback/").unwrap_err();
+    /// assert!(error.to_string().contains("must match exactly"));
+    /// assert!(registered.with_redirect("https://example.com/callback").is_ok());
+    /// ```
+    pub fn with_redirect(mut self, uri: &str) -> Result<Self> {
+        let registered = if self.redirect_uris.is_empty() {
+            uri == self.redirect
+        } else {
+            self.redirect_uris
+                .iter()
+                .any(|registered| registered == uri)
+        };
+
+        if !registered {
+            return Err(Error::RedirectMismatch(uri.to_owned()));
+        }
+
+        self.redirect = uri.to_owned();
+
+        Ok(self)
+    }
+
+    /// Returns the full url needed for authorisation. This needs to be opened
+    /// in a browser.
+    ///
+    /// The url uses [PKCE](https://tools.ietf.org/html/rfc7636) with a code
+    /// verifier kept by this `Registered`, so the code it returns has to be
+    /// passed to `complete` on the same, or a deserialised copy of it. The
+    /// verifier is left out if PKCE was turned off with `Registration::pkce`.
+    pub fn authorize_url(&self) -> String {
+        self.authorize_url_with(&AuthorizeRequest::new())
+    }
+
+    /// `authorize_url` with the extra options of `request`.
+    ///
+    /// ```
+    /// use mammut::registration::{AuthorizeRequest, Registration};
+    ///
+    /// let registered = Registration::from_app(
+    ///     "https://mastodon.social",
+    ///     "CLIENT_ID",
+    ///     "CLIENT_SECRET",
+    ///     "https://example.com/callback",
+    ///     Default::default(),
+    /// )
+    /// .build()
+    /// .unwrap();
+    /// let request = AuthorizeRequest::new().force_login().state("xyz").lang("de");
+    /// let url = registered.authorize_url_with(&request);
+    /// assert!(url.ends_with("&force_login=true&state=xyz&lang=de"));
+    /// ```
+    pub fn authorize_url_with(&self, request: &AuthorizeRequest) -> String {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        query
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect)
+            .append_pair("scope", &self.scopes.to_string())
+            .append_pair("response_type", "code");
+
+        if let Some(ref code_verifier) = self.code_verifier {
+            query
+                .append_pair("code_challenge", &code_challenge(code_verifier))
+                .append_pair("code_challenge_method", "S256");
+        }
+
+        query.extend_pairs(request.pairs());
+
+        format!("{}/oauth/authorize?{}", self.base, query.finish())
+    }
+
+    /// Create an access token from the code given to the user after opening
+    /// `authorize_url`.
+    pub fn complete(&self, code: &str) -> Result<Mastodon> {
+        let mut form = vec![
+            ("code", code.to_owned()),
+            ("grant_type", "authorization_code".to_owned()),
+        ];
+
+        if let Some(ref code_verifier) = self.code_verifier {
+            form.push(("code_verifier", code_verifier.clone()));
+        }
+
+        let token = self.request_token(form)?;
+
+        self.to_mastodon(token, TokenKind::User)
+    }
+
+    /// Create an access token for the app itself, without a user. This can
+    /// be used for routes that need a token but not a user, such as reading
+    /// the public timelines of instances that require authentication.
+    ///
+    /// Routes that act for a user, such as `verify_credentials`, fail with
+    /// this token.
+    pub fn create_app_token(&self) -> Result<Mastodon> {
+        let form = vec![("grant_type", "client_credentials".to_owned())];
+        let token = self.request_token(form)?;
+
+        self.to_mastodon(token, TokenKind::App)
+    }
+
+    /// Create an access token for a user from their email and password,
+    /// without opening a browser. This is only meant for trusted tools, such
+    /// as one run by an instance's own admin.
+    ///
+    /// It doesn't work for accounts with two-factor authentication enabled,
+    /// which have to use `authorize_url` and `complete` instead.
+    ///
+    /// # Errors
+    /// A wrong email or password is returned as an `Error::Api` for which
+    /// `Error::is_invalid_grant` is true, so it can be told apart from a
+    /// network failure.
+    ///
+    /// ```no_run
+    /// # extern crate mammut;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use mammut::Registration;
+    ///
+    /// let registered = Registration::new("https://mastodon.social").build()?;
+    /// match registered.login_with_password("user@example.com", "hunter2") {
+    ///     Ok(mastodon) => println!("{:?}", mastodon.verify_credentials()?),
+    ///     Err(ref e) if e.is_invalid_grant() => println!("Wrong email or password"),
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn login_with_password(&self, email: &str, password: &str) -> Result<Mastodon> {
+        let form = vec![
+            ("grant_type", "password".to_owned()),
+            ("username", email.to_owned()),
+            ("password", password.to_owned()),
+        ];
+        let token = self.request_token(form)?;
+
+        self.to_mastodon(token, TokenKind::User)
+    }
+
+    // Request an access token with the app's credentials and scopes added to
+    // `form`. The scopes must be the same as when authorising, or the
+    // instance refuses the code.
+    fn request_token(&self, mut form: Vec<(&'static str, String)>) -> Result<String> {
+        let url = format!("{}/oauth/token", self.base);
+        form.extend(vec![
+            ("client_id", self.client_id.clone()),
+            ("client_secret", self.client_secret.clone()),
+            ("redirect_uri", self.redirect.clone()),
+            ("scope", self.scopes.to_string()),
+        ]);
+
+        let headers = user_agent_headers(self.user_agent.as_deref())?;
+        let response = self.client.post(&url).headers(headers).form(&form).send()?;
+        let token: AccessToken = deserialise(response)?;
+
+        Ok(token.access_token)
+    }
+
+    fn to_mastodon(&self, token: String, token_kind: TokenKind) -> Result<Mastodon> {
+        let mut mastodon = Mastodon::from_registration(
+            self.base.clone(),
+            self.client_id.clone(),
+            self.client_secret.clone(),
+            self.redirect.clone(),
+            token,
+            token_kind,
+            self.client.clone(),
         );
+        mastodon
+            .headers
+            .extend(user_agent_headers(self.user_agent.as_deref())?);
+
+        Ok(mastodon)
+    }
+}
+
+/// Extra options for the url from `Registered::authorize_url_with`.
+#[derive(Clone, Debug, Default)]
+pub struct AuthorizeRequest {
+    force_login: bool,
+    state: Option<String>,
+    lang: Option<String>,
+}
+
+impl AuthorizeRequest {
+    /// Construct a new `AuthorizeRequest` object
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the user to log in even if they're already logged in to the
+    /// instance in their browser, so another account can be added.
+    pub fn force_login(mut self) -> Self {
+        self.force_login = true;
+        self
+    }
+
+    /// Set a value the instance sends back with the code when redirecting,
+    /// to match the redirect to the request that caused it.
+    pub fn state<I: Into<String>>(mut self, state: I) -> Self {
+        self.state = Some(state.into());
+        self
+    }
+
+    /// Set the language of the authorisation page, such as `de`.
+    pub fn lang<I: Into<String>>(mut self, lang: I) -> Self {
+        self.lang = Some(lang.into());
+        self
+    }
+
+    fn pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
+
+        if self.force_login {
+            pairs.push(("force_login", "true".to_owned()));
+        }
+
+        if let Some(ref state) = self.state {
+            pairs.push(("state", state.clone()));
+        }
+
+        if let Some(ref lang) = self.lang {
+            pairs.push(("lang", lang.clone()));
+        }
+
+        pairs
+    }
+}
+
+/// The code and state the instance redirected back with after the user
+/// authorised the app.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Authorization {
+    /// The code to pass to `Registered::complete`.
+    pub code: String,
+    /// The state given with `AuthorizeRequest::state`, if any.
+    pub state: Option<String>,
+}
+
+impl Authorization {
+    /// Get the code and state out of the url the instance redirected to.
+    ///
+    /// # Errors
+    /// If the user refused to authorise the app, the error the instance
+    /// redirected with, such as `access_denied`, is returned as an
+    /// `Error::Api`. A url without a code is returned as an `invalid_request`
+    /// `Error::Api`.
+    ///
+    /// ```
+    /// use mammut::registration::Authorization;
+    ///
+    /// let authorization =
+    ///     Authorization::from_redirect("https://example.com/callback?code=abc&state=xyz").unwrap();
+    /// assert_eq!(authorization.code, "abc");
+    /// assert_eq!(authorization.state.as_deref(), Some("xyz"));
+    ///
+    /// let error = Authorization::from_redirect(
+    ///     "https://example.com/callback?error=access_denied&state=xyz",
+    /// )
+    /// .unwrap_err();
+    /// assert!(error.to_string().contains("access_denied"));
+    /// ```
+    pub fn from_redirect(url: &str) -> Result<Self> {
+        let url = Url::parse(url)?;
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+
+        if let Some(error) = param("error") {
+            return Err(Error::Api(ApiError {
+                error: Some(error),
+                error_description: param("error_description"),
+                status: None,
+            }));
+        }
+
+        match param("code") {
+            Some(code) => Ok(Authorization {
+                code,
+                state: param("state"),
+            }),
+            None => Err(Error::Api(ApiError {
+                error: Some("invalid_request".to_owned()),
+                error_description: Some("The redirect url has no code".to_owned()),
+                status: None,
+            })),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Webfinger {
+    #[serde(default)]
+    links: Vec<WebfingerLink>,
+}
+
+#[derive(Deserialize)]
+struct WebfingerLink {
+    rel: String,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    href: Option<String>,
+}
 
-        let token: AccessToken = self.client.post(&url).send()?.json()?;
+/// Find the base url of the instance an account is on from its handle, such
+/// as `@Gargron@mastodon.social`, with a webfinger lookup on the handle's
+/// domain. This lets users log in by typing their handle, including on
+/// instances that use a different domain for handles than for the server.
+///
+/// The base url can be given to `Registration::new`.
+///
+/// ```no_run
+/// # extern crate mammut;
+/// # fn main() -> mammut::Result<()> {
+/// use mammut::registration::{self, Registration};
+///
+/// let base = registration::discover_instance("@Gargron@mastodon.social")?;
+/// let registered = Registration::new(base).client_name("mammut-example").build()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+/// A handle without a user and domain is returned as an
+/// `Error::InvalidHandle` without contacting any server:
+///
+/// ```
+/// # extern crate mammut;
+/// use mammut::{registration, Error};
+///
+/// match registration::discover_instance("mastodon.social") {
+///     Err(Error::InvalidHandle(ref handle)) => assert_eq!(handle, "mastodon.social"),
+///     _ => panic!("the handle has no user"),
+/// }
+/// ```
+///
+/// An account that doesn't exist is usually an `Error::Client` for which
+/// `Error::is_not_found` is true.
+pub fn discover_instance(handle: &str) -> Result<String> {
+    let acct = handle.trim().trim_start_matches('@');
+    let invalid = || Error::InvalidHandle(handle.to_owned());
+    let at = acct.rfind('@').ok_or_else(invalid)?;
+    let (user, domain) = (&acct[..at], &acct[at + 1..]);
 
-        Ok(Mastodon::from_registration(
-            self.base,
-            self.client_id.unwrap(),
-            self.client_secret.unwrap(),
-            self.redirect.unwrap(),
-            token.access_token,
-            self.client,
-        ))
+    if user.is_empty() || user.contains('@') || domain.is_empty() || domain.contains('/') {
+        return Err(invalid());
     }
+
+    let url = Url::parse_with_params(
+        &format!("https://{}/.well-known/webfinger", domain),
+        &[("resource", format!("acct:{}", acct))],
+    )?;
+    let response = Client::new().get(url).send()?;
+
+    // The domain may redirect to the server, so fall back to where the
+    // lookup ended up if there is no link to the account itself.
+    let fallback = response.url().origin().ascii_serialization();
+    let webfinger: Webfinger = deserialise(response)?;
+
+    let base = webfinger
+        .links
+        .iter()
+        .filter(|link| link.rel == "self")
+        .filter(|link| {
+            link.kind.as_deref().map_or(false, |kind| {
+                kind == "application/activity+json" || kind.starts_with("application/ld+json")
+            })
+        })
+        .filter_map(|link| Url::parse(link.href.as_ref()?).ok())
+        .map(|href| href.origin().ascii_serialization())
+        .next();
+
+    Ok(base.unwrap_or(fallback))
+}
+
+// The headers to send the user agent with, if one was set.
+fn user_agent_headers(user_agent: Option<&str>) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    if let Some(user_agent) = user_agent {
+        headers.insert(header::USER_AGENT, HeaderValue::from_str(user_agent)?);
+    }
+
+    Ok(headers)
+}
+
+// A random PKCE code verifier, 32 random bytes encoded as 43 characters of
+// unpadded url safe base64.
+fn code_verifier() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+}
+
+// The `S256` code challenge derived from a PKCE code verifier.
+fn code_challenge(code_verifier: &str) -> String {
+    let digest = Sha256::digest(code_verifier.as_bytes());
+    base64::encode_config(&digest, base64::URL_SAFE_NO_PAD)
 }
@@ -0,0 +1,131 @@
+/// A builder pattern struct for reporting an account.
+///
+/// ```
+/// use mammut::report_builder::{ReportBuilder, ReportCategory};
+///
+/// let report = ReportBuilder::new("1".into())
+///     .status_ids(vec!["103".into(), "104".into()])
+///     .category(ReportCategory::Violation)
+///     .rule_ids(vec!["2".into()])
+///     .forward(true);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ReportBuilder {
+    /// The ID of the account to report.
+    pub account_id: String,
+    /// The IDs of statuses of the account to attach to the report.
+    pub status_ids: Vec<String>,
+    /// The reason for the report.
+    pub comment: Option<String>,
+    /// Whether to also send the report to the account's instance, if it is a
+    /// remote account.
+    pub forward: Option<bool>,
+    /// The kind of problem being reported.
+    pub category: Option<ReportCategory>,
+    /// The IDs of the instance's rules that were broken, when `category` is
+    /// `Violation`.
+    pub rule_ids: Vec<String>,
+}
+
+/// The kind of problem a report is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ReportCategory {
+    /// Unwanted or repetitive content.
+    #[serde(rename = "spam")]
+    Spam,
+    /// Content that is illegal.
+    #[serde(rename = "legal")]
+    Legal,
+    /// Content that breaks the rules of the instance.
+    #[serde(rename = "violation")]
+    Violation,
+    /// Any other problem.
+    #[serde(rename = "other")]
+    Other,
+    /// A category unknown to this crate, such as one added by a newer
+    /// instance. Isn't sent when reporting.
+    #[serde(other, skip_serializing)]
+    Unknown,
+}
+
+impl ReportCategory {
+    fn as_str(self) -> Option<&'static str> {
+        match self {
+            ReportCategory::Spam => Some("spam"),
+            ReportCategory::Legal => Some("legal"),
+            ReportCategory::Violation => Some("violation"),
+            ReportCategory::Other => Some("other"),
+            ReportCategory::Unknown => None,
+        }
+    }
+}
+
+impl ReportBuilder {
+    /// Create a new report of an account.
+    pub fn new(account_id: String) -> Self {
+        ReportBuilder {
+            account_id,
+            ..Self::default()
+        }
+    }
+
+    /// Attach statuses of the account to the report.
+    pub fn status_ids(mut self, status_ids: Vec<String>) -> Self {
+        self.status_ids = status_ids;
+        self
+    }
+
+    /// Set the reason for the report.
+    pub fn comment(mut self, comment: String) -> Self {
+        self.comment = Some(comment);
+        self
+    }
+
+    /// Set whether to also send the report to the account's instance.
+    pub fn forward(mut self, forward: bool) -> Self {
+        self.forward = Some(forward);
+        self
+    }
+
+    /// Set the kind of problem being reported.
+    pub fn category(mut self, category: ReportCategory) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    /// Set the rules that were broken, by the IDs from
+    /// `Mastodon::instance_rules`. Only used when the category is
+    /// `Violation`.
+    pub fn rule_ids(mut self, rule_ids: Vec<String>) -> Self {
+        self.rule_ids = rule_ids;
+        self
+    }
+
+    /// Turn the report into form fields, with the arrays as `status_ids[]`
+    /// and `rule_ids[]`.
+    pub(crate) fn into_form(self) -> Vec<(&'static str, String)> {
+        let mut form = vec![("account_id", self.account_id)];
+
+        for status_id in self.status_ids {
+            form.push(("status_ids[]", status_id));
+        }
+
+        if let Some(comment) = self.comment {
+            form.push(("comment", comment));
+        }
+
+        if let Some(forward) = self.forward {
+            form.push(("forward", forward.to_string()));
+        }
+
+        if let Some(category) = self.category.and_then(ReportCategory::as_str) {
+            form.push(("category", category.to_owned()));
+        }
+
+        for rule_id in self.rule_ids {
+            form.push(("rule_ids[]", rule_id));
+        }
+
+        form
+    }
+}
@@ -19,10 +19,31 @@ pub struct StatusBuilder {
     /// Visibility of the status, defaults to `Public`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub visibility: Option<Visibility>,
+    /// ISO 639 language code of the status, such as `en`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// A unique key, such as a UUID, sent as the `Idempotency-Key` header by
+    /// `Mastodon::new_status`. Posting again with the same key within an
+    /// hour returns the status posted the first time instead of a duplicate,
+    /// so a request that failed part way can be safely retried.
+    #[serde(skip)]
+    pub idempotency_key: Option<String>,
 }
 
 /// The visibility of a status.
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+///
+/// Visibilities this crate doesn't know about, such as those added by other
+/// fediverse software, are deserialised as `Unknown` instead of failing.
+///
+/// ```
+/// # extern crate mammut;
+/// # extern crate serde_json;
+/// use mammut::status_builder::Visibility;
+///
+/// let visibility: Visibility = serde_json::from_str(r#""local""#).unwrap();
+/// assert_eq!(visibility, Visibility::Unknown);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Visibility {
     /// A Direct message to a user
     #[serde(rename = "direct")]
@@ -34,8 +55,12 @@ pub enum Visibility {
     #[serde(rename = "unlisted")]
     Unlisted,
     /// Posted to public timelines
+    #[default]
     #[serde(rename = "public")]
     Public,
+    /// A visibility unknown to this crate. Can't be used when posting a status.
+    #[serde(other, skip_serializing)]
+    Unknown,
 }
 
 impl StatusBuilder {
@@ -47,14 +72,8 @@ impl StatusBuilder {
     /// ```
     pub fn new(status: String) -> Self {
         StatusBuilder {
-            status: status,
+            status,
             ..Self::default()
         }
     }
 }
-
-impl Default for Visibility {
-    fn default() -> Self {
-        Visibility::Public
-    }
-}